@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::{parse_number, split_once, Error, ParseError},
+    solution::Part,
+};
+
+pub const PATH: &str = "answers.toml";
+
+// Expected answers keyed by day and part, read from a TOML
+// subset: `[dNN]` sections with `part1` and `part2` integer
+// keys. Blank lines and `#` comments are ignored.
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers(BTreeMap<(u32, Part), usize>);
+
+impl Answers {
+    pub fn read(path: &str) -> Result<Self, Error> {
+        let input = std::fs::read_to_string(path)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+
+        Ok(Self::parse(&input)?)
+    }
+
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut answers = BTreeMap::new();
+        let mut day = None;
+
+        for (i, line) in input.lines().enumerate() {
+            let content = line.split('#').next().unwrap_or_default().trim();
+
+            if content.is_empty() {
+                continue;
+            }
+
+            if content.starts_with('[') {
+                day = Some(section(line, content).map_err(|e| e.at_line(i + 1))?);
+            } else {
+                let (key, value) = answer(line, content, day).map_err(|e| e.at_line(i + 1))?;
+                answers.insert(key, value);
+            }
+        }
+
+        return Ok(Self(answers));
+
+        fn section(line: &str, content: &str) -> Result<u32, ParseError> {
+            content
+                .strip_prefix("[d")
+                .and_then(|section| section.strip_suffix(']'))
+                .and_then(|n| n.parse::<u32>().ok())
+                .filter(|n| (1..=25).contains(n))
+                .ok_or_else(|| ParseError::at(line, content, "a section '[d01]' to '[d25]'"))
+        }
+
+        fn answer(
+            line: &str,
+            content: &str,
+            day: Option<u32>,
+        ) -> Result<((u32, Part), usize), ParseError> {
+            let (key, value) = split_once(line, content, "=")?;
+            let key = key.trim();
+            let value = value.trim();
+
+            let day = day.ok_or_else(|| ParseError::at(line, key, "a section header"))?;
+            let part = match key {
+                "part1" => Part::One,
+                "part2" => Part::Two,
+                _ => return Err(ParseError::at(line, key, "'part1' or 'part2'")),
+            };
+
+            Ok(((day, part), parse_number(line, value)?))
+        }
+    }
+
+    pub fn get(&self, day: u32, part: Part) -> Option<usize> {
+        self.0.get(&(day, part)).copied()
+    }
+}
+
+// Expected answer for the given day and part
+// from the answers file in the repository.
+#[cfg(test)]
+pub fn expected(day: u32, part: Part) -> usize {
+    Answers::read(PATH)
+        .expect("answers file")
+        .get(day, part)
+        .unwrap_or_else(|| panic!("no answer for day {} part {}", day, part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            "# comment
+
+             [d01]
+             part1 = 1316 # trailing comment
+             part2 = 1344
+
+             [d25]
+             part1 = 549",
+        )
+        .unwrap();
+
+        assert_eq!(answers.get(1, Part::One), Some(1316));
+        assert_eq!(answers.get(1, Part::Two), Some(1344));
+        assert_eq!(answers.get(25, Part::One), Some(549));
+        assert_eq!(answers.get(25, Part::Two), None);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let e = Answers::parse("[d01]\npart1 = 1316\npart3 = 1344")
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 1: expected 'part1' or 'part2', found 'part3'"
+        );
+
+        let e = Answers::parse("part1 = 1316").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 1: expected a section header, found 'part1'"
+        );
+
+        let e = Answers::parse("[d26]").err().unwrap();
+        assert_eq!(e.line(), Some(1));
+
+        let e = Answers::parse("[d01]\npart1 = x").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 9: expected a number, found 'x'"
+        );
+    }
+
+    fn verify(day: u32) {
+        let answers = Answers::read(PATH).unwrap();
+        let day = solution::day(day).unwrap();
+        let input = std::fs::read_to_string(day.input_path()).expect("file not found");
+        let input = day.parse(&input).unwrap();
+
+        for part in Part::BOTH {
+            if let Some(expected) = answers.get(day.day, part) {
+                assert_eq!(day.solve(input.as_ref(), part), expected, "part {}", part);
+            }
+        }
+    }
+
+    macro_rules! verify {
+        ($($name:ident: $day:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    verify($day);
+                }
+            )*
+        };
+    }
+
+    verify! {
+        test_day01: 1,
+        test_day02: 2,
+        test_day03: 3,
+        test_day04: 4,
+        test_day05: 5,
+        test_day06: 6,
+        test_day07: 7,
+        test_day08: 8,
+        test_day09: 9,
+        test_day10: 10,
+        test_day11: 11,
+        test_day12: 12,
+        test_day13: 13,
+        test_day14: 14,
+        test_day15: 15,
+        test_day16: 16,
+        test_day17: 17,
+        test_day18: 18,
+        test_day19: 19,
+        test_day20: 20,
+        test_day21: 21,
+        test_day22: 22,
+        test_day23: 23,
+        test_day24: 24,
+        test_day25: 25,
+    }
+}
@@ -1,5 +1,24 @@
-pub fn run(measurements: &[i64]) -> (usize, usize) {
-    (part1(measurements) as usize, part2(measurements) as usize)
+use crate::{
+    error::Error,
+    solution::{parse_lines, Solution},
+};
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    type Input = Vec<i64>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input)
+    }
+
+    fn part1(measurements: &Self::Input) -> usize {
+        part1(measurements) as usize
+    }
+
+    fn part2(measurements: &Self::Input) -> usize {
+        part2(measurements) as usize
+    }
 }
 
 fn part1(measurements: &[i64]) -> u32 {
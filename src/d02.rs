@@ -1,6 +1,11 @@
 use std::str::FromStr;
 use Direction::*;
 
+use crate::{
+    error::{parse_number, split_once, Error, ParseError},
+    solution::{parse_lines, Solution},
+};
+
 pub enum Direction {
     Forward,
     Down,
@@ -9,8 +14,22 @@ pub enum Direction {
 
 pub struct Input(Direction, u32);
 
-pub fn run(ins: &[Input]) -> (usize, usize) {
-    (part1(ins) as usize, part2(ins) as usize)
+pub struct Day02;
+
+impl Solution for Day02 {
+    type Input = Vec<Input>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input)
+    }
+
+    fn part1(ins: &Self::Input) -> usize {
+        part1(ins) as usize
+    }
+
+    fn part2(ins: &Self::Input) -> usize {
+        part2(ins) as usize
+    }
 }
 
 fn part1(ins: &[Input]) -> u32 {
@@ -37,26 +56,21 @@ fn part2(ins: &[Input]) -> u32 {
 }
 
 impl FromStr for Input {
-    type Err = std::io::Error;
+    type Err = ParseError;
 
     fn from_str(line: &str) -> Result<Self, Self::Err> {
-        let input = line
-            .split_once(' ')
-            .map(|(dir, dist)| {
-                let dist = dist.parse::<u32>().unwrap();
-
-                let dir = match dir {
-                    "forward" => Direction::Forward,
-                    "down" => Direction::Down,
-                    "up" => Direction::Up,
-                    _ => unreachable!(),
-                };
-
-                Input(dir, dist)
-            })
-            .unwrap();
-
-        Ok(input)
+        let (dir, dist) = split_once(line, line, " ")?;
+
+        let dir = match dir {
+            "forward" => Direction::Forward,
+            "down" => Direction::Down,
+            "up" => Direction::Up,
+            _ => return Err(ParseError::at(line, dir, "'forward', 'down' or 'up'")),
+        };
+
+        let dist = parse_number::<u32>(line, dist)?;
+
+        Ok(Input(dir, dist))
     }
 }
 
@@ -95,4 +109,16 @@ mod tests {
 
         assert_eq!(900, res);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let e = "backward 5".parse::<Input>().err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 1: expected 'forward', 'down' or 'up', found 'backward'"
+        );
+
+        let e = "down five".parse::<Input>().err().unwrap();
+        assert_eq!(e.to_string(), "column 6: expected a number, found 'five'");
+    }
 }
@@ -1,13 +1,29 @@
 use Type::*;
 
+use crate::{error::Error, solution::Solution};
+
 #[derive(Clone, Copy)]
 enum Type {
     Oxygen,
     Co2,
 }
 
-pub fn run(ins: &[&str]) -> (usize, usize) {
-    (part1(ins) as usize, part2(ins) as usize)
+pub struct Day03;
+
+impl Solution for Day03 {
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part1(ins: &Self::Input) -> usize {
+        part1(&ins.iter().map(String::as_str).collect::<Vec<_>>()) as usize
+    }
+
+    fn part2(ins: &Self::Input) -> usize {
+        part2(&ins.iter().map(String::as_str).collect::<Vec<_>>()) as usize
+    }
 }
 
 fn part1(words: &[&str]) -> u32 {
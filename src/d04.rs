@@ -1,5 +1,10 @@
 use std::collections::HashMap;
 
+use crate::{
+    error::{parse_number, Error, ParseError},
+    solution::Solution,
+};
+
 const SIZE: usize = 5;
 
 #[derive(Debug, Clone, Copy)]
@@ -24,7 +29,7 @@ impl Default for Item {
 }
 
 #[derive(Debug, Clone)]
-struct Board {
+pub struct Board {
     size: usize,
     rows: [[Item; SIZE]; SIZE],
     index: HashMap<u32, (usize, usize)>,
@@ -85,30 +90,48 @@ impl Board {
     }
 }
 
-impl From<&[&str]> for Board {
-    fn from(board: &[&str]) -> Self {
-        let mut rows = [[Item::default(); SIZE]; SIZE];
+impl Board {
+    // Line numbers of errors are relative to the first row.
+    fn parse(rows: &[&str]) -> Result<Self, ParseError> {
+        let mut items = [[Item::default(); SIZE]; SIZE];
         let mut index = HashMap::new();
 
-        board
-            .iter()
-            .map(|row| row.trim().replace("  ", " "))
-            .enumerate()
-            .for_each(|(i, row)| {
-                row.split(' ')
-                    .map(|n| n.parse::<u32>().unwrap())
-                    .enumerate()
-                    .for_each(|(j, n)| {
-                        index.insert(n, (i, j));
-                        rows[i][j].n = n;
-                    });
-            });
+        for (i, row) in rows.iter().enumerate() {
+            if i == SIZE {
+                return Err(ParseError::at(row, row, "an empty line").at_line(i + 1));
+            }
 
-        Self {
-            size: rows.len(),
-            rows,
-            index,
+            let mut numbers = row.split_whitespace();
+
+            for (j, item) in items[i].iter_mut().enumerate() {
+                let n = numbers
+                    .next()
+                    .ok_or_else(|| ParseError::at(row, &row[row.len()..], "a number"))
+                    .and_then(|n| parse_number(row, n))
+                    .map_err(|e| e.at_line(i + 1))?;
+
+                index.insert(n, (i, j));
+                item.n = n;
+            }
+
+            if let Some(n) = numbers.next() {
+                return Err(ParseError::at(row, n, "end of line").at_line(i + 1));
+            }
+        }
+
+        if rows.len() < SIZE {
+            let last = rows.last().copied().unwrap_or_default();
+            let expected = format!("{} rows of numbers", SIZE);
+            return Err(
+                ParseError::at(last, &last[last.len()..], expected).at_line(rows.len().max(1))
+            );
         }
+
+        Ok(Self {
+            size: items.len(),
+            rows: items,
+            index,
+        })
     }
 }
 
@@ -145,21 +168,57 @@ fn part2(draws: &[u32], boards: &mut [Board]) -> u32 {
     unreachable!();
 }
 
-pub fn run(lines: &[&str]) -> (usize, usize) {
-    let draws = lines[0]
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Input = (Vec<u32>, Vec<Board>);
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1((draws, boards): &Self::Input) -> usize {
+        part1(draws, &mut boards.clone()) as usize
+    }
+
+    fn part2((draws, boards): &Self::Input) -> usize {
+        part2(draws, &mut boards.clone()) as usize
+    }
+}
+
+fn parse(input: &str) -> Result<(Vec<u32>, Vec<Board>), ParseError> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let line = |i: usize| lines.get(i).copied().unwrap_or(&input[input.len()..]);
+
+    let first = line(0);
+    let draws = first
         .split(',')
-        .map(|c| c.parse::<u32>().unwrap())
-        .collect::<Vec<_>>();
-
-    let mut boards = lines[2..]
-        .split(|line| line.is_empty())
-        .map(Board::from)
-        .collect::<Vec<_>>();
-
-    (
-        part1(&draws, &mut boards) as usize,
-        part2(&draws, &mut boards) as usize,
-    )
+        .map(|n| parse_number(first, n))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.at_line(1))?;
+
+    if !line(1).is_empty() {
+        return Err(ParseError::at(line(1), line(1), "an empty line").at_line(2));
+    }
+
+    let mut boards = vec![];
+    let mut start = 2;
+
+    while start < lines.len() {
+        let end = lines[start..]
+            .iter()
+            .position(|line| line.is_empty())
+            .map_or(lines.len(), |len| start + len);
+
+        boards.push(Board::parse(&lines[start..end]).map_err(|e| e.after_lines(start))?);
+        start = end + 1;
+    }
+
+    if boards.is_empty() {
+        return Err(ParseError::at(line(2), line(2), "a board").at_line(3));
+    }
+
+    Ok((draws, boards))
 }
 
 #[cfg(test)]
@@ -188,8 +247,48 @@ mod tests {
 22 11 13  6  5
  2  0 12  3  7";
 
-        let input = input.split('\n').collect::<Vec<_>>();
+        let (draws, mut boards) = parse(input).unwrap();
+
+        assert_eq!(part1(&draws, &mut boards), 4512);
+        assert_eq!(part2(&draws, &mut boards), 1924);
+    }
 
-        assert_eq!(run(&input[..]), (4512, 1924));
+    #[test]
+    fn test_parse_error() {
+        let e = parse("x\n").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 1: expected a number, found 'x'"
+        );
+
+        let e = parse("1,2\n").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 1: expected a board, found end of line"
+        );
+
+        let board = "1 2 3 4 5\n6 7 8 9 10\n11 12 13 14 15\n16 17 18 19 20\n21 22 23 24 25";
+
+        let e = parse(&format!("1,2\n\n{}\n\n{}", board, board.replace("19", "x")))
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 12, column 10: expected a number, found 'x'"
+        );
+
+        let e = parse(&format!("1,2\n\n{} 26", board)).err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 7, column 16: expected end of line, found '26'"
+        );
+
+        let e = parse(&format!("1,2\n\n{}", &board[..board.rfind('\n').unwrap()]))
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 6, column 15: expected 5 rows of numbers, found end of line"
+        );
     }
 }
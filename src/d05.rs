@@ -1,20 +1,25 @@
 use std::{collections::HashMap, str::FromStr};
 
+use crate::{
+    error::{parse_number, split_once, Error, ParseError},
+    solution::{parse_lines, Solution},
+};
+
 pub struct Point {
     x: i32,
     y: i32,
 }
 
 impl FromStr for Point {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(s.split_once(',')
-            .map(|(x, y)| Self {
-                x: x.parse().unwrap(),
-                y: y.parse().unwrap(),
-            })
-            .unwrap())
+        let (x, y) = split_once(s, s, ",")?;
+
+        Ok(Self {
+            x: parse_number(s, x)?,
+            y: parse_number(s, y)?,
+        })
     }
 }
 
@@ -24,15 +29,15 @@ pub struct Line {
 }
 
 impl FromStr for Line {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(s.split_once(" -> ")
-            .map(|(p1, p2)| Self {
-                start: p1.parse().unwrap(),
-                end: p2.parse().unwrap(),
-            })
-            .unwrap())
+        let (p1, p2) = split_once(s, s, " -> ")?;
+
+        Ok(Self {
+            start: p1.parse().map_err(|e: ParseError| e.within(s, p1))?,
+            end: p2.parse().map_err(|e: ParseError| e.within(s, p2))?,
+        })
     }
 }
 
@@ -60,8 +65,22 @@ impl Line {
     }
 }
 
-pub fn run(lines: &[Line]) -> (usize, usize) {
-    (part1(lines) as usize, part2(lines) as usize)
+pub struct Day05;
+
+impl Solution for Day05 {
+    type Input = Vec<Line>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input)
+    }
+
+    fn part1(lines: &Self::Input) -> usize {
+        part1(lines) as usize
+    }
+
+    fn part2(lines: &Self::Input) -> usize {
+        part2(lines) as usize
+    }
 }
 
 fn part1(lines: &[Line]) -> u32 {
@@ -128,4 +147,22 @@ mod tests {
 
         assert_eq!(12, part2(&input));
     }
+
+    #[test]
+    fn test_parse_error() {
+        let e = "0,9 -> 5;9".parse::<Line>().err().unwrap();
+        assert_eq!(e.to_string(), "column 8: expected ',', found '5;9'");
+
+        let e = "0,9 => 5,9".parse::<Line>().err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 1: expected ' -> ', found '0,9 => 5,9'"
+        );
+
+        let e = parse_lines::<Line>("0,9 -> 5,9\n8,0 -> 0,x").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "parse error in line 2, column 10: expected a number, found 'x'"
+        );
+    }
 }
@@ -1,5 +1,24 @@
-pub fn run(lines: &[usize]) -> (usize, usize) {
-    (part1(lines), part2(lines))
+use crate::{
+    error::{parse_number, Error, ParseError},
+    solution::Solution,
+};
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(parse(input.trim_end()).map_err(|e| e.at_line(1))?)
+    }
+
+    fn part1(fish: &Self::Input) -> usize {
+        part1(fish)
+    }
+
+    fn part2(fish: &Self::Input) -> usize {
+        part2(fish)
+    }
 }
 
 fn part1(input: &[usize]) -> usize {
@@ -27,9 +46,12 @@ fn simulate(fish: &[usize], runs: usize) -> usize {
     fish_per_day.iter().sum()
 }
 
-pub fn parse(line: &str) -> Vec<usize> {
+pub fn parse(line: &str) -> Result<Vec<usize>, ParseError> {
     line.split(',')
-        .map(|f| f.parse::<usize>().unwrap())
+        .map(|f| match parse_number(line, f) {
+            Ok(timer @ 0..=8) => Ok(timer),
+            _ => Err(ParseError::at(line, f, "a timer from 0 to 8")),
+        })
         .collect()
 }
 
@@ -59,4 +81,21 @@ mod tests {
 
         assert_eq!(part2(&input), 26984457539);
     }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(parse(INPUT).unwrap(), vec![3, 4, 3, 1, 2]);
+
+        let e = parse("3,4,9,1").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 5: expected a timer from 0 to 8, found '9'"
+        );
+
+        let e = Day06::parse("3,x\n").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "parse error in line 1, column 3: expected a timer from 0 to 8, found 'x'"
+        );
+    }
 }
@@ -1,18 +1,39 @@
-pub fn run(line: &[i32]) -> (usize, usize) {
-    (part1(line) as usize, part2(line) as usize)
+use crate::{
+    error::{parse_number, Error, ParseError},
+    solution::Solution,
+};
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    type Input = Vec<i32>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(parse(input.trim_end()).map_err(|e| e.at_line(1))?)
+    }
+
+    fn part1(positions: &Self::Input) -> usize {
+        part1(positions) as usize
+    }
+
+    fn part2(positions: &Self::Input) -> usize {
+        part2(positions) as usize
+    }
 }
 
-pub fn parse(line: &str) -> Vec<i32> {
+pub fn parse(line: &str) -> Result<Vec<i32>, ParseError> {
     line.split(',')
-        .map(|d| d.parse::<i32>().unwrap())
-        .collect::<Vec<_>>()
+        .map(|d| match parse_number(line, d) {
+            Ok(position) if position >= 0 => Ok(position),
+            _ => Err(ParseError::at(line, d, "a position of at least 0")),
+        })
+        .collect()
 }
 
 fn part1(positions: &[i32]) -> i32 {
     let max_position = positions.iter().max().unwrap();
 
-    (0..*max_position)
-        .into_iter()
+    (0..=*max_position)
         .map(|x| positions.iter().map(|position| (x - position).abs()).sum())
         .min()
         .unwrap()
@@ -28,8 +49,7 @@ fn part1_median(positions: &[i32]) -> i32 {
 fn part2(positions: &[i32]) -> i32 {
     let max_position = positions.iter().max().unwrap();
 
-    (0..*max_position)
-        .into_iter()
+    (0..=*max_position)
         .map(|x| {
             positions
                 .iter()
@@ -69,4 +89,23 @@ mod tests {
 
         assert_eq!(part2(&positions), 168);
     }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(parse("0").unwrap(), vec![0]);
+        assert_eq!(part1(&[0]), 0);
+        assert_eq!(part2(&[0]), 0);
+
+        let e = parse("16,-1,2").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 4: expected a position of at least 0, found '-1'"
+        );
+
+        let e = Day07::parse("").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "parse error in line 1, column 1: expected a position of at least 0, found end of line"
+        );
+    }
 }
@@ -1,7 +1,26 @@
 use std::{collections::HashSet, str::FromStr};
 
-pub fn run(inputs: &[Input]) -> (usize, usize) {
-    (part1(inputs) as usize, part2(inputs) as usize)
+use crate::{
+    error::{split_once, Error, ParseError},
+    solution::{parse_lines, Solution},
+};
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    type Input = Vec<Input>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input)
+    }
+
+    fn part1(inputs: &Self::Input) -> usize {
+        part1(inputs) as usize
+    }
+
+    fn part2(inputs: &Self::Input) -> usize {
+        part2(inputs) as usize
+    }
 }
 
 #[derive(Debug)]
@@ -11,12 +30,23 @@ pub struct Input {
 }
 
 impl FromStr for Input {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (input, output) = s.split_once(" | ").unwrap();
-        let input = input.split(' ').map(String::from).collect::<Vec<_>>();
-        let output = output.split(' ').map(String::from).collect::<Vec<_>>();
+        fn words(s: &str, part: &str) -> Result<Vec<String>, ParseError> {
+            part.split(' ')
+                .map(
+                    |word| match word.chars().all(|c| ('a'..='g').contains(&c)) {
+                        true if !word.is_empty() => Ok(String::from(word)),
+                        _ => Err(ParseError::at(s, word, "segments 'a' to 'g'")),
+                    },
+                )
+                .collect()
+        }
+
+        let (input, output) = split_once(s, s, " | ")?;
+        let input = words(s, input)?;
+        let output = words(s, output)?;
 
         Ok(Input { input, output })
     }
@@ -138,4 +168,19 @@ mod tests {
 
         assert_eq!(part2(&input), 61229);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "be cfbegad | fdgacbe".parse::<Input>().unwrap();
+        assert_eq!(input.input, vec!["be", "cfbegad"]);
+
+        let e = "be cfbegad fdgacbe".parse::<Input>().err().unwrap();
+        assert_eq!(e.column(), 1);
+
+        let e = "be cfbxgad | fdgacbe".parse::<Input>().err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 4: expected segments 'a' to 'g', found 'cfbxgad'"
+        );
+    }
 }
@@ -1,9 +1,25 @@
 use std::collections::{HashSet, VecDeque};
 
-pub fn run(field: Vec<Vec<u8>>) -> (usize, usize) {
-    let field = field.iter().map(|line| line.as_slice()).collect::<Vec<_>>();
+use crate::{error::Error, solution::Solution};
 
-    (part1(&field).0 as usize, part2(&field) as usize)
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input = Vec<Vec<u8>>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(input.lines().map(parse).collect())
+    }
+
+    fn part1(field: &Self::Input) -> usize {
+        let field = field.iter().map(|line| line.as_slice()).collect::<Vec<_>>();
+        part1(&field).0 as usize
+    }
+
+    fn part2(field: &Self::Input) -> usize {
+        let field = field.iter().map(|line| line.as_slice()).collect::<Vec<_>>();
+        part2(&field) as usize
+    }
 }
 
 fn part1(field: &[&[u8]]) -> (u32, Vec<(usize, usize)>) {
@@ -116,7 +132,7 @@ mod tests {
             .map(|line| parse(line))
             .collect::<Vec<_>>();
 
-        assert_eq!(run(input).0, 15);
+        assert_eq!(Day09::part1(&input), 15);
     }
 
     #[test]
@@ -130,6 +146,6 @@ mod tests {
             })
             .collect::<Vec<_>>();
 
-        assert_eq!(run(input).1, 1134);
+        assert_eq!(Day09::part2(&input), 1134);
     }
 }
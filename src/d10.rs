@@ -1,5 +1,21 @@
-pub fn run(lines: &[&str]) -> (usize, usize) {
-    (part1(lines) as usize, part2(lines))
+use crate::{error::Error, solution::Solution};
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part1(lines: &Self::Input) -> usize {
+        part1(&lines.iter().map(String::as_str).collect::<Vec<_>>()) as usize
+    }
+
+    fn part2(lines: &Self::Input) -> usize {
+        part2(&lines.iter().map(String::as_str).collect::<Vec<_>>())
+    }
 }
 
 fn part1(lines: &[&str]) -> u32 {
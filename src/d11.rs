@@ -1,10 +1,26 @@
+use crate::{
+    error::{Error, ParseError},
+    solution::Solution,
+};
+
 const SIZE: usize = 10;
 
-pub fn run(lines: &[&str]) -> (usize, usize) {
-    (
-        part1(&mut parse(lines)) as usize,
-        part2(&mut parse(lines)) as usize,
-    )
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = [[u8; SIZE]; SIZE];
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(parse(&input.trim_end().lines().collect::<Vec<_>>())?)
+    }
+
+    fn part1(field: &Self::Input) -> usize {
+        part1(&mut field.clone()) as usize
+    }
+
+    fn part2(field: &Self::Input) -> usize {
+        part2(&mut field.clone()) as usize
+    }
 }
 
 fn part1(field: &mut [[u8; SIZE]; SIZE]) -> u32 {
@@ -90,17 +106,46 @@ fn flash(
     }
 }
 
-fn parse(lines: &[&str]) -> [[u8; SIZE]; SIZE] {
+fn parse(lines: &[&str]) -> Result<[[u8; SIZE]; SIZE], ParseError> {
     let mut res = [[0; SIZE]; SIZE];
 
-    lines.iter().enumerate().for_each(|(row, line)| {
-        line.trim()
-            .bytes()
-            .enumerate()
-            .for_each(|(col, b)| res[row][col] = b - b'0')
-    });
+    for (row, line) in lines.iter().enumerate() {
+        if row == SIZE {
+            return Err(ParseError::at(line, line, "end of input").at_line(row + 1));
+        }
+
+        let trimmed = line.trim();
+        let tokens = trimmed
+            .char_indices()
+            .map(|(i, c)| &trimmed[i..i + c.len_utf8()])
+            .collect::<Vec<_>>();
+
+        for (col, n) in res[row].iter_mut().enumerate() {
+            let token = tokens
+                .get(col)
+                .copied()
+                .unwrap_or(&trimmed[trimmed.len()..]);
+
+            *n = match token.chars().next().and_then(|c| c.to_digit(10)) {
+                Some(digit) => digit as u8,
+                None => return Err(ParseError::at(line, token, "a digit").at_line(row + 1)),
+            };
+        }
 
-    res
+        if let Some(token) = tokens.get(SIZE) {
+            return Err(ParseError::at(line, token, "end of line").at_line(row + 1));
+        }
+    }
+
+    if lines.len() < SIZE {
+        return Err(ParseError::new(
+            1,
+            format!("{} lines", SIZE),
+            lines.len().to_string(),
+        ));
+    }
+
+    Ok(res)
 }
 
 #[cfg(test)]
@@ -120,12 +165,42 @@ mod tests {
     #[test]
     fn test_part1() {
         let input = INPUT.split('\n').collect::<Vec<_>>();
-        assert_eq!(run(&input).0, 1656);
+        assert_eq!(part1(&mut parse(&input).unwrap()), 1656);
     }
 
     #[test]
     fn test_part2() {
         let input = INPUT.split('\n').collect::<Vec<_>>();
-        assert_eq!(run(&input).1, 195);
+        assert_eq!(part2(&mut parse(&input).unwrap()), 195);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = INPUT.split('\n').collect::<Vec<_>>();
+
+        let mut lines = input.clone();
+        lines[2] = "526455617x";
+        let e = parse(&lines).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 10: expected a digit, found 'x'"
+        );
+
+        lines[2] = "52645561";
+        let e = parse(&lines).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 9: expected a digit, found end of line"
+        );
+
+        lines[2] = "52645561730";
+        let e = parse(&lines).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 11: expected end of line, found '0'"
+        );
+
+        let e = parse(&input[..9]).unwrap_err();
+        assert_eq!(e.to_string(), "column 1: expected 10 lines, found 9");
     }
 }
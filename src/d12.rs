@@ -1,13 +1,15 @@
 use graph::prelude::*;
 use indexmap::IndexSet;
 
+use crate::{error::Error, solution::Solution};
+
 #[derive(Debug)]
 enum CaveSize {
     Big,
     Small,
 }
 
-struct CaveSystem {
+pub struct CaveSystem {
     g: UndirectedCsrGraph<usize, CaveSize>,
     ids: IndexSet<String>,
 }
@@ -70,10 +72,24 @@ impl From<&[&str]> for CaveSystem {
     }
 }
 
-pub fn run(lines: &[&str]) -> (usize, usize) {
-    let cave_system = CaveSystem::from(lines);
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = CaveSystem;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(CaveSystem::from(
+            input.lines().collect::<Vec<_>>().as_slice(),
+        ))
+    }
 
-    (part1(&cave_system) as usize, part2(&cave_system) as usize)
+    fn part1(cave_system: &Self::Input) -> usize {
+        part1(cave_system) as usize
+    }
+
+    fn part2(cave_system: &Self::Input) -> usize {
+        part2(cave_system) as usize
+    }
 }
 
 fn part1(cave_system: &CaveSystem) -> u32 {
@@ -1,12 +1,29 @@
 use std::collections::HashSet;
 
+use crate::{
+    error::{parse_number, split_once, Error, ParseError},
+    solution::Solution,
+};
+
 type Coords = Vec<(u32, u32)>;
 type Folds = Vec<(char, u32)>;
 
-pub fn run(lines: &str) -> (usize, usize) {
-    let (coords, folds) = parse(lines);
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = (Coords, Folds);
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1((coords, folds): &Self::Input) -> usize {
+        part1(coords, folds)
+    }
 
-    (part1(&coords, &folds), part2(&coords, &folds))
+    fn part2((coords, folds): &Self::Input) -> usize {
+        part2(coords, folds)
+    }
 }
 
 fn part1(coords: &Coords, folds: &Folds) -> usize {
@@ -25,8 +42,9 @@ fn part2(coords: &Coords, folds: &Folds) -> usize {
         .iter()
         .for_each(|(x, y)| m[*y as usize][*x as usize] = 'x');
 
+    // The code is drawn to stderr to keep stdout machine-readable.
     m.iter()
-        .for_each(|row| println!("{}", row.iter().collect::<String>()));
+        .for_each(|row| eprintln!("{}", row.iter().collect::<String>()));
 
     0
 }
@@ -46,26 +64,59 @@ fn fold(coords: &Coords, (axis, line): (char, u32)) -> Coords {
         .collect::<Vec<_>>()
 }
 
-fn parse(lines: &str) -> (Coords, Folds) {
-    let (coords, folds) = lines.split_once("\n\n").unwrap();
-
-    let coords = coords
-        .split('\n')
-        .map(|line| line.trim())
-        .map(|line| line.split_once(',').unwrap())
-        .map(|(x, y)| (x.parse::<u32>().unwrap(), y.parse::<u32>().unwrap()))
-        .collect::<Vec<_>>();
-
-    let folds = folds
-        .split('\n')
-        .filter(|line| !line.is_empty())
-        .map(|line| line.trim())
-        .map(|line| line.split(' ').nth(2).unwrap())
-        .map(|fold| fold.split_once('=').unwrap())
-        .map(|(axis, pos)| (axis.chars().next().unwrap(), pos.parse::<u32>().unwrap()))
-        .collect::<Vec<_>>();
-
-    (coords, folds)
+fn parse(input: &str) -> Result<(Coords, Folds), ParseError> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let blank = lines
+        .iter()
+        .position(|line| line.is_empty())
+        .unwrap_or(lines.len());
+
+    let coords = lines[..blank]
+        .iter()
+        .enumerate()
+        .map(|(i, line)| parse_dot(line).map_err(|e| e.at_line(i + 1)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let folds = lines
+        .iter()
+        .enumerate()
+        .skip(blank + 1)
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| parse_fold(line).map_err(|e| e.at_line(i + 1)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if coords.is_empty() {
+        let line = lines.first().copied().unwrap_or(input);
+        return Err(ParseError::at(line, line, "a dot").at_line(1));
+    }
+
+    if folds.is_empty() {
+        let end = &input[input.len()..];
+        return Err(ParseError::at(end, end, "a fold instruction").at_line(lines.len() + 1));
+    }
+
+    Ok((coords, folds))
+}
+
+fn parse_dot(line: &str) -> Result<(u32, u32), ParseError> {
+    let (x, y) = split_once(line, line.trim(), ",")?;
+
+    Ok((parse_number(line, x)?, parse_number(line, y)?))
+}
+
+fn parse_fold(line: &str) -> Result<(char, u32), ParseError> {
+    let fold = line.trim();
+    let along = fold
+        .strip_prefix("fold along ")
+        .ok_or_else(|| ParseError::at(line, fold, "'fold along'"))?;
+    let (axis, position) = split_once(line, along, "=")?;
+    let axis = match axis {
+        "x" => 'x',
+        "y" => 'y',
+        _ => return Err(ParseError::at(line, axis, "'x' or 'y'")),
+    };
+
+    Ok((axis, parse_number(line, position)?))
 }
 
 #[cfg(test)]
@@ -95,8 +146,41 @@ mod tests {
                            fold along x=5"#;
     #[test]
     fn test_part1() {
-        let (coords, folds) = parse(INPUT);
+        let (coords, folds) = parse(INPUT).unwrap();
 
         assert_eq!(part1(&coords, &folds), 17);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let e = parse("6,10\n0;14\n\nfold along y=7").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 1: expected ',', found '0;14'"
+        );
+
+        let e = parse("6,10\n0,x\n\nfold along y=7").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 3: expected a number, found 'x'"
+        );
+
+        let e = parse("6,10\n\nfold along z=7").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 12: expected 'x' or 'y', found 'z'"
+        );
+
+        let e = parse("6,10\n\nfold y=7").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 1: expected 'fold along', found 'fold y=7'"
+        );
+
+        let e = parse("6,10\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 1: expected a fold instruction, found end of line"
+        );
+    }
 }
@@ -1,9 +1,28 @@
 use std::collections::HashMap;
 
-pub fn run(input: &str) -> (usize, usize) {
-    let (template, rules) = parse(input);
+use crate::{
+    error::{split_once, Error, ParseError},
+    solution::Solution,
+};
 
-    (part1(&template, &rules, 10), part2(&template, &rules, 40))
+type Rules = HashMap<(char, char), char>;
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = (Vec<char>, Rules);
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1((template, rules): &Self::Input) -> usize {
+        part1(template, rules, 10)
+    }
+
+    fn part2((template, rules): &Self::Input) -> usize {
+        part2(template, rules, 40)
+    }
 }
 
 // vec-based
@@ -64,24 +83,61 @@ fn part2(template: &[char], rules: &HashMap<(char, char), char>, steps: u32) ->
     char_counts.values().max().unwrap() - char_counts.values().min().unwrap()
 }
 
-fn parse(input: &str) -> (Vec<char>, HashMap<(char, char), char>) {
-    let (template, rules) = input.split_once("\n\n").unwrap();
+fn parse(input: &str) -> Result<(Vec<char>, Rules), ParseError> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let line = |i: usize| lines.get(i).copied().unwrap_or(&input[input.len()..]);
+
+    let template = line(0).chars().collect::<Vec<_>>();
+    if template.is_empty() {
+        return Err(ParseError::at(line(0), line(0), "a polymer template").at_line(1));
+    }
+
+    if !line(1).is_empty() {
+        return Err(ParseError::at(line(1), line(1), "an empty line").at_line(2));
+    }
+
+    let rules = lines
+        .iter()
+        .enumerate()
+        .skip(2)
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| parse_rule(line).map_err(|e| e.at_line(i + 1)))
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    // Every pair which can occur during insertion needs a rule.
+    let mut elements = template.clone();
+    elements.extend(rules.values());
+    elements.sort_unstable();
+    elements.dedup();
+
+    for left in &elements {
+        for right in &elements {
+            if !rules.contains_key(&(*left, *right)) {
+                let end = &input[input.len()..];
+                return Err(
+                    ParseError::at(end, end, format!("a rule for '{}{}'", left, right))
+                        .at_line(lines.len() + 1),
+                );
+            }
+        }
+    }
+
+    Ok((template, rules))
+}
 
-    let template = template.chars().collect::<Vec<_>>();
+fn parse_rule(line: &str) -> Result<((char, char), char), ParseError> {
+    let (pair, insertion) = split_once(line, line.trim(), " -> ")?;
 
-    let rules = rules
-        .split('\n')
-        .filter(|line| !line.is_empty())
-        .map(|line| line.trim().split_once(" -> ").unwrap())
-        .map(|(pair, insertion)| {
-            let mut pair = pair.chars();
-            let pair = (pair.next().unwrap(), pair.next().unwrap());
-            let insertion = insertion.chars().next().unwrap();
-            (pair, insertion)
-        })
-        .collect::<HashMap<_, _>>();
+    let pair = match pair.chars().collect::<Vec<_>>()[..] {
+        [left, right] => (left, right),
+        _ => return Err(ParseError::at(line, pair, "a pair of elements")),
+    };
+    let insertion = match insertion.chars().collect::<Vec<_>>()[..] {
+        [element] => element,
+        _ => return Err(ParseError::at(line, insertion, "an element")),
+    };
 
-    (template, rules)
+    Ok((pair, insertion))
 }
 
 #[cfg(test)]
@@ -109,15 +165,42 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let (template, rules) = parse(INPUT);
+        let (template, rules) = parse(INPUT).unwrap();
 
         assert_eq!(part1(&template, &rules, 10), 1588);
     }
 
     #[test]
     fn test_part2() {
-        let (template, rules) = parse(INPUT);
+        let (template, rules) = parse(INPUT).unwrap();
 
         assert_eq!(part2(&template, &rules, 40), 2188189693529);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let e = parse("NN\n\nNN -> C\nNC => B").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 4, column 1: expected ' -> ', found 'NC => B'"
+        );
+
+        let e = parse("NN\n\nNNN -> C").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 1: expected a pair of elements, found 'NNN'"
+        );
+
+        let e = parse("NN\n\nNN -> C\nNC -> B").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 5, column 1: expected a rule for 'BB', found end of line"
+        );
+
+        let e = parse("").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 1: expected a polymer template, found end of line"
+        );
+    }
 }
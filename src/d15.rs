@@ -2,6 +2,8 @@ use std::{cmp::Ordering, collections::BinaryHeap};
 
 use graph::prelude::*;
 
+use crate::{error::Error, solution::Solution};
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 struct State {
     cost: u32,
@@ -23,14 +25,26 @@ impl PartialOrd for State {
     }
 }
 
-pub fn run(lines: &str) -> (usize, usize) {
-    let g1 = parse(lines, 1);
-    let g2 = parse(lines, 5);
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = (
+        UndirectedCsrGraph<usize, (), u32>,
+        UndirectedCsrGraph<usize, (), u32>,
+    );
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let input = input.trim();
+        Ok((parse(input, 1), parse(input, 5)))
+    }
 
-    (
-        dijkstra(&g1, 0, g1.node_count() - 1) as usize,
-        dijkstra(&g2, 0, g2.node_count() - 1) as usize,
-    )
+    fn part1((g1, _): &Self::Input) -> usize {
+        dijkstra(g1, 0, g1.node_count() - 1) as usize
+    }
+
+    fn part2((_, g2): &Self::Input) -> usize {
+        dijkstra(g2, 0, g2.node_count() - 1) as usize
+    }
 }
 
 fn dijkstra(g: &UndirectedCsrGraph<usize, (), u32>, start: usize, end: usize) -> u32 {
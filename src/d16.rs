@@ -1,121 +1,669 @@
-pub fn run(line: &str) -> (usize, usize) {
-    let line = parse(line);
+use std::{
+    fmt::Display,
+    io::{ErrorKind, Read},
+    str::FromStr,
+};
 
-    (part1(&line), part2(&line))
+use crate::{
+    error::{parse_number, Error, ParseError},
+    solution::Solution,
+};
+
+pub struct Day16;
+
+// The outermost packet and the value it evaluates to.
+pub struct Transmission {
+    packet: Packet,
+    value: usize,
 }
 
-fn part1(line: &str) -> usize {
-    parse_packet(line).0
+impl Solution for Day16 {
+    type Input = Transmission;
+
+    const SOLVES_IN_PARSE: bool = true;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let packet = parse(input)?;
+        let value = packet
+            .evaluate()
+            .ok_or_else(|| Error::NoSolution("the value of the transmission overflows".into()))?;
+
+        Ok(Transmission { packet, value })
+    }
+
+    fn part1(transmission: &Self::Input) -> usize {
+        part1(&transmission.packet)
+    }
+
+    fn part2(transmission: &Self::Input) -> usize {
+        transmission.value
+    }
 }
 
-fn part2(line: &str) -> usize {
-    parse_packet(line).2
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Sum,
+    Product,
+    Min,
+    Max,
+    Gt,
+    Lt,
+    Eq,
 }
 
-fn parse_packet(p: &str) -> (usize, usize, usize) {
-    let mut offset = 0;
-    let (version, read) = parse_version(p);
-    offset += read;
-    let (type_id, read) = parse_type_id(&p[offset..]);
-    offset += read;
+impl Op {
+    fn from_type_id(type_id: u8) -> Self {
+        match type_id {
+            0 => Op::Sum,
+            1 => Op::Product,
+            2 => Op::Min,
+            3 => Op::Max,
+            5 => Op::Gt,
+            6 => Op::Lt,
+            7 => Op::Eq,
+            _ => unreachable!(),
+        }
+    }
 
-    let (version_sum, read, value) = match type_id {
-        4 => parse_literal(&p[offset..]),
-        _ => {
-            let (version_sum, read, values) = parse_operator(&p[offset..]);
-
-            let value = match type_id {
-                0 => values.iter().sum(),
-                1 => values.iter().product(),
-                2 => *values.iter().min().unwrap(),
-                3 => *values.iter().max().unwrap(),
-                5 => (values[0] > values[1]) as usize,
-                6 => (values[0] < values[1]) as usize,
-                7 => (values[0] == values[1]) as usize,
-                _ => unreachable!(),
-            };
+    fn type_id(&self) -> u8 {
+        match self {
+            Op::Sum => 0,
+            Op::Product => 1,
+            Op::Min => 2,
+            Op::Max => 3,
+            Op::Gt => 5,
+            Op::Lt => 6,
+            Op::Eq => 7,
+        }
+    }
+
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        [
+            Op::Sum,
+            Op::Product,
+            Op::Min,
+            Op::Max,
+            Op::Gt,
+            Op::Lt,
+            Op::Eq,
+        ]
+        .into_iter()
+        .find(|op| op.symbol() == symbol)
+    }
+
+    // Describes the expected number of sub-packets
+    // if the operator cannot be applied to `count`.
+    fn arity(&self, count: usize) -> Option<String> {
+        match self {
+            Op::Gt | Op::Lt | Op::Eq if count != 2 => {
+                Some(format!("2 sub-packets for '{}'", self.symbol()))
+            }
+            _ if count == 0 => Some(format!("sub-packets for '{}'", self.symbol())),
+            _ => None,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Op::Sum => "+",
+            Op::Product => "*",
+            Op::Min => "min",
+            Op::Max => "max",
+            Op::Gt => ">",
+            Op::Lt => "<",
+            Op::Eq => "=",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Packet {
+    Literal {
+        version: u8,
+        value: usize,
+    },
+    Operator {
+        version: u8,
+        op: Op,
+        children: Vec<Packet>,
+    },
+}
+
+impl Packet {
+    fn version_sum(&self) -> usize {
+        match self {
+            Packet::Literal { version, .. } => *version as usize,
+            Packet::Operator {
+                version, children, ..
+            } => *version as usize + children.iter().map(Packet::version_sum).sum::<usize>(),
+        }
+    }
+
+    // Returns `None` if the value overflows or
+    // an operator has the wrong number of operands.
+    pub fn evaluate(&self) -> Option<usize> {
+        let (op, children) = match self {
+            Packet::Literal { value, .. } => return Some(*value),
+            Packet::Operator { op, children, .. } => (op, children),
+        };
+
+        if op.arity(children.len()).is_some() {
+            return None;
+        }
+
+        let values = children
+            .iter()
+            .map(Packet::evaluate)
+            .collect::<Option<Vec<_>>>()?;
 
-            (version_sum, read, value)
+        match op {
+            Op::Sum => values
+                .iter()
+                .try_fold(0_usize, |sum, v| sum.checked_add(*v)),
+            Op::Product => values
+                .iter()
+                .try_fold(1_usize, |product, v| product.checked_mul(*v)),
+            Op::Min => values.iter().copied().min(),
+            Op::Max => values.iter().copied().max(),
+            Op::Gt => Some((values[0] > values[1]) as usize),
+            Op::Lt => Some((values[0] < values[1]) as usize),
+            Op::Eq => Some((values[0] == values[1]) as usize),
+        }
+    }
+}
+
+// Renders the packet as an S-expression, e.g. `(+ 1 (* 2 3))`.
+impl Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::Literal { value, .. } => write!(f, "{}", value),
+            Packet::Operator { op, children, .. } => {
+                write!(f, "({}", op.symbol())?;
+                for child in children {
+                    write!(f, " {}", child)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+// Parses an S-expression as rendered by `Display`,
+// all packets are assigned version 0.
+impl FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (packet, rem) = expression(s, s)?;
+        let rem = rem.trim_start();
+
+        if !rem.is_empty() {
+            return Err(ParseError::at(s, token(rem), "end of line"));
+        }
+
+        Ok(packet)
+    }
+}
+
+fn expression<'a>(s: &str, rem: &'a str) -> Result<(Packet, &'a str), ParseError> {
+    let rem = rem.trim_start();
+
+    let mut rem = match rem.strip_prefix('(') {
+        Some(rem) => rem.trim_start(),
+        None => {
+            let number = token(rem);
+            let value = parse_number(s, number)?;
+            return Ok((Packet::Literal { version: 0, value }, &rem[number.len()..]));
         }
     };
 
-    offset += read;
+    let symbol = token(rem);
+    let op = Op::from_symbol(symbol).ok_or_else(|| ParseError::at(s, symbol, "an operator"))?;
+    rem = &rem[symbol.len()..];
+
+    let mut children = vec![];
+
+    loop {
+        rem = rem.trim_start();
 
-    return (version as usize + version_sum, offset, value);
+        if rem.is_empty() {
+            return Err(ParseError::at(s, rem, "')'"));
+        }
+
+        if let Some(rest) = rem.strip_prefix(')') {
+            if let Some(expected) = op.arity(children.len()) {
+                return Err(ParseError::at(s, &rem[..1], expected));
+            }
+            let packet = Packet::Operator {
+                version: 0,
+                op,
+                children,
+            };
+            return Ok((packet, rest));
+        }
 
-    fn parse_version(p: &str) -> (u32, usize) {
-        (u32::from_str_radix(&p[0..3], 2).unwrap(), 3)
+        let (child, next) = expression(s, rem)?;
+        children.push(child);
+        rem = next;
     }
+}
 
-    fn parse_type_id(p: &str) -> (u8, usize) {
-        (u8::from_str_radix(&p[0..3], 2).unwrap(), 3)
+// The next token up to a whitespace or parenthesis,
+// or a single character if it is a parenthesis.
+fn token(s: &str) -> &str {
+    match s.find(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+        Some(0) => &s[..1],
+        Some(end) => &s[..end],
+        None => s,
     }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthType {
+    Bits,
+    Packets,
+}
+
+// A packet tree BITS cannot represent.
+#[derive(Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    // A version that exceeds 3 bits.
+    Version(u8),
+    // An operator with more sub-packets than either length field holds.
+    SubPackets(usize),
+}
 
-    fn parse_length_type_id(p: &str) -> (u8, usize) {
-        (u8::from_str_radix(&p[0..1], 2).unwrap(), 1)
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Version(version) => write!(f, "version {} exceeds 3 bits", version),
+            Self::SubPackets(count) => {
+                write!(f, "{} sub-packets exceed both length fields", count)
+            }
+        }
     }
+}
 
-    fn parse_literal(mut p: &str) -> (usize, usize, usize) {
-        let mut number = String::new();
-        let mut read = 0;
+impl From<EncodeError> for Error {
+    fn from(e: EncodeError) -> Self {
+        Error::NoSolution(format!("cannot encode packet, {}", e))
+    }
+}
 
-        loop {
-            number.push_str(&p[1..5]);
-            read += 5;
-            if matches!(&p[0..1], "1") {
-                p = &p[5..];
-            } else {
-                break;
+// Encodes the packet as a hex transmission. Operators use the given
+// length type unless their sub-packets exceed its length field.
+pub fn encode(packet: &Packet, length_type: LengthType) -> Result<String, EncodeError> {
+    let mut bits = String::new();
+    write_packet(&mut bits, packet, length_type)?;
+
+    // Transmissions are padded to whole bytes.
+    while !bits.len().is_multiple_of(8) {
+        bits.push('0');
+    }
+
+    Ok((0..bits.len())
+        .step_by(4)
+        .map(|i| format!("{:X}", u8::from_str_radix(&bits[i..i + 4], 2).unwrap()))
+        .collect())
+}
+
+fn write_packet(
+    bits: &mut String,
+    packet: &Packet,
+    length_type: LengthType,
+) -> Result<(), EncodeError> {
+    match packet {
+        Packet::Literal { version, value } => {
+            if *version >= 8 {
+                return Err(EncodeError::Version(*version));
+            }
+            bits.push_str(&format!("{:03b}100", version));
+
+            let groups = ((usize::BITS - value.leading_zeros()) as usize)
+                .div_ceil(4)
+                .max(1);
+
+            for group in (0..groups).rev() {
+                let more = (group > 0) as u8;
+                bits.push_str(&format!("{}{:04b}", more, (value >> (4 * group)) & 0xF));
             }
         }
+        Packet::Operator {
+            version,
+            op,
+            children,
+        } => {
+            if *version >= 8 {
+                return Err(EncodeError::Version(*version));
+            }
+            bits.push_str(&format!("{:03b}{:03b}", version, op.type_id()));
+
+            let mut sub = String::new();
+            for child in children {
+                write_packet(&mut sub, child, length_type)?;
+            }
+
+            let fits_packets = children.len() < 1 << 11;
+            let fits_bits = sub.len() < 1 << 15;
 
-        (0, read, usize::from_str_radix(number.as_str(), 2).unwrap())
+            match (length_type, fits_packets, fits_bits) {
+                (LengthType::Packets, true, _) | (LengthType::Bits, true, false) => {
+                    bits.push_str(&format!("1{:011b}", children.len()))
+                }
+                (_, _, true) => bits.push_str(&format!("0{:015b}", sub.len())),
+                _ => return Err(EncodeError::SubPackets(children.len())),
+            }
+
+            bits.push_str(&sub);
+        }
     }
 
-    fn parse_operator(p: &str) -> (usize, usize, Vec<usize>) {
-        let mut offset = 0;
-        let mut version_sum = 0;
+    Ok(())
+}
+
+fn part1(packet: &Packet) -> usize {
+    packet.version_sum()
+}
+
+// Number of bytes read from the source at once.
+const CHUNK: usize = 4096;
+
+// Reads bits from hex digits, most significant bit first. Digits are
+// pulled from the source as needed, whitespace in between is skipped.
+struct Reader<R> {
+    source: R,
+    hex: Vec<u8>,
+    // column of each digit in `hex`, counting every byte of the source
+    columns: Vec<usize>,
+    // position in bits within `hex`
+    pos: usize,
+    // number of digits already dropped from `hex`
+    offset: usize,
+    // number of bytes read from the source
+    read: usize,
+    // column following the last digit read
+    end: usize,
+    eof: bool,
+}
 
-        let (length_type_id, read) = parse_length_type_id(p);
-        offset += read;
+impl<R: Read> Reader<R> {
+    fn new(source: R) -> Self {
+        Self {
+            source,
+            hex: vec![],
+            columns: vec![],
+            pos: 0,
+            offset: 0,
+            read: 0,
+            end: 1,
+            eof: false,
+        }
+    }
 
-        let mut values = vec![];
+    // Column of the hex digit containing the given bit.
+    fn column(&self, pos: usize) -> usize {
+        self.columns.get(pos / 4).copied().unwrap_or(self.end)
+    }
 
-        if length_type_id == 0 {
-            let bits = u32::from_str_radix(&p[1..16], 2).unwrap();
+    // Makes sure the given number of digits is buffered,
+    // returns false if the source ends before.
+    fn fill(&mut self, digits: usize) -> Result<bool, Error> {
+        while self.hex.len() < digits && !self.eof {
+            let mut chunk = [0; CHUNK];
+            let read = match self.source.read(&mut chunk) {
+                Ok(read) => read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            self.eof = read == 0;
 
-            offset += 15;
-            let boundary = offset + bits as usize;
+            for (i, b) in chunk[..read].iter().enumerate() {
+                let column = self.read + i + 1;
 
-            while offset < boundary as usize {
-                let (version, read, value) = parse_packet(&p[offset..]);
-                values.push(value);
-                offset += read;
-                version_sum += version;
+                if b.is_ascii_whitespace() {
+                    continue;
+                }
+                if !b.is_ascii_hexdigit() {
+                    let found = format!("'{}'", b.escape_ascii());
+                    return Err(ParseError::new(column, "a hex digit", found).into());
+                }
+                self.hex.push(*b);
+                self.columns.push(column);
+                self.end = column + 1;
             }
-        } else {
-            let packets = u32::from_str_radix(&p[1..12], 2).unwrap();
+            self.read += read;
+        }
+
+        Ok(self.hex.len() >= digits)
+    }
+
+    fn bit(&self, pos: usize) -> usize {
+        let digit = match self.hex[pos / 4] {
+            b @ b'0'..=b'9' => b - b'0',
+            b @ b'A'..=b'F' => b - b'A' + 10,
+            b => b - b'a' + 10,
+        };
+        ((digit >> (3 - pos % 4)) & 1) as usize
+    }
+
+    // Reads up to 64 bits as a number.
+    fn read(&mut self, n: usize) -> Result<usize, Error> {
+        if self.pos + n > self.hex.len() * 4 && !self.fill((self.pos + n).div_ceil(4))? {
+            let missing = self.pos + n - self.hex.len() * 4;
+            return Err(ParseError::new(
+                self.end,
+                format!(
+                    "{} more bit{}",
+                    missing,
+                    if missing == 1 { "" } else { "s" }
+                ),
+                "end of line",
+            )
+            .into());
+        }
+
+        let value = (self.pos..self.pos + n).fold(0, |value, pos| value << 1 | self.bit(pos));
+        self.pos += n;
+
+        Ok(value)
+    }
+
+    // Position of the first set bit from the current position up to
+    // the given number of bits or the end of the source.
+    fn set_bit(&mut self, bits: Option<usize>) -> Result<Option<usize>, Error> {
+        let mut pos = self.pos;
+
+        while bits.is_none_or(|bits| pos < self.pos + bits) && self.fill(pos / 4 + 1)? {
+            if self.bit(pos) == 1 {
+                return Ok(Some(pos));
+            }
+            pos += 1;
+        }
+
+        Ok(None)
+    }
+
+    // Skips the zero padding up to the next whole byte
+    // and drops all digits which have been read.
+    fn align(&mut self) -> Result<(), Error> {
+        let padding = (8 - (self.offset * 4 + self.pos) % 8) % 8;
+
+        if let Some(pos) = self.set_bit(Some(padding))? {
+            return Err(ParseError::new(self.column(pos), "zero padding", "a set bit").into());
+        }
+
+        self.pos = (self.pos + padding).min(self.hex.len() * 4);
+
+        let digits = self.pos / 4;
+        self.hex.drain(..digits);
+        self.columns.drain(..digits);
+        self.offset += digits;
+        self.pos -= digits * 4;
+
+        Ok(())
+    }
+}
+
+// Decodes top-level packets one after another as they are read from
+// the source. Each packet is padded with zeros to a whole byte, the
+// transmission may end with any number of zeros.
+pub struct Decoder<R> {
+    reader: Reader<R>,
+    done: bool,
+}
+
+impl<R: Read> Decoder<R> {
+    pub fn new(source: R) -> Self {
+        Self {
+            reader: Reader::new(source),
+            done: false,
+        }
+    }
+
+    fn decode(&mut self) -> Result<Option<Packet>, Error> {
+        if self.reader.set_bit(None)?.is_none() {
+            return Ok(None);
+        }
+
+        let packet = parse_packet(&mut self.reader)?;
+        self.reader.align()?;
+
+        Ok(Some(packet))
+    }
+}
+
+impl<R: Read> Iterator for Decoder<R> {
+    type Item = Result<Packet, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let packet = self.decode().transpose();
+        self.done = !matches!(packet, Some(Ok(_)));
+
+        packet
+    }
+}
+
+fn parse_packet<R: Read>(reader: &mut Reader<R>) -> Result<Packet, Error> {
+    let start = reader.pos;
+    let version = reader.read(3)? as u8;
+    let type_id = reader.read(3)? as u8;
+
+    let packet = match type_id {
+        4 => Packet::Literal {
+            version,
+            value: parse_literal(reader)?,
+        },
+        _ => {
+            let op = Op::from_type_id(type_id);
+            let children = parse_operator(reader)?;
+
+            if let Some(expected) = op.arity(children.len()) {
+                return Err(ParseError::new(
+                    reader.column(start),
+                    expected,
+                    children.len().to_string(),
+                )
+                .into());
+            }
+
+            Packet::Operator {
+                version,
+                op,
+                children,
+            }
+        }
+    };
 
-            offset += 11;
+    return Ok(packet);
+
+    fn parse_literal<R: Read>(reader: &mut Reader<R>) -> Result<usize, Error> {
+        let start = reader.pos;
+        let mut value = 0_usize;
+
+        loop {
+            let more = reader.read(1)? == 1;
+            let group = reader.read(4)?;
+
+            if value.leading_zeros() < 4 {
+                return Err(ParseError::new(
+                    reader.column(start),
+                    format!("a literal of at most {} bits", usize::BITS),
+                    "a wider literal",
+                )
+                .into());
+            }
+            value = value << 4 | group;
+
+            if !more {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn parse_operator<R: Read>(reader: &mut Reader<R>) -> Result<Vec<Packet>, Error> {
+        let mut children = vec![];
+
+        if reader.read(1)? == 0 {
+            let bits = reader.read(15)?;
+            let start = reader.pos;
+            let boundary = start + bits;
+
+            while reader.pos < boundary {
+                children.push(parse_packet(reader)?);
+            }
+
+            if reader.pos != boundary {
+                return Err(ParseError::new(
+                    reader.column(start),
+                    format!("sub-packets of {} bits", bits),
+                    format!("{} bits", reader.pos - start),
+                )
+                .into());
+            }
+        } else {
+            let packets = reader.read(11)?;
 
             for _ in 0..packets {
-                let (version, read, value) = parse_packet(&p[offset..]);
-                values.push(value);
-                offset += read;
-                version_sum += version;
+                children.push(parse_packet(reader)?);
             }
         }
 
-        (version_sum, offset, values)
+        Ok(children)
     }
 }
 
-fn parse(line: &str) -> String {
-    line.trim()
-        .chars()
-        .map(|c| c.to_digit(16).unwrap())
-        .map(|c| format!("{:04b}", c))
-        .collect::<String>()
+// Decodes a single transmission, all bits after
+// the packet have to be zero.
+fn parse(line: &str) -> Result<Packet, ParseError> {
+    let hex = line.trim();
+
+    if let Some((i, c)) = hex.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(ParseError::at(
+            line,
+            &hex[i..i + c.len_utf8()],
+            "a hex digit",
+        ));
+    }
+
+    let mut reader = Reader::new(hex.as_bytes());
+
+    let packet = parse_packet(&mut reader)
+        .and_then(|packet| match reader.set_bit(None)? {
+            Some(pos) => {
+                Err(ParseError::new(reader.column(pos), "zero padding", "a set bit").into())
+            }
+            None => Ok(packet),
+        })
+        .map_err(|e| match e {
+            Error::Parse(e) => e.within(line, hex),
+            e => unreachable!("reading from a slice: {}", e),
+        })?;
+
+    Ok(packet)
 }
 
 #[cfg(test)]
@@ -127,70 +675,445 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let input = parse("D2FE28");
+        let input = parse("D2FE28").unwrap();
         assert_eq!(part1(&input), 6);
 
-        let input = parse("8A004A801A8002F478");
+        let input = parse("8A004A801A8002F478").unwrap();
         assert_eq!(part1(&input), 16);
 
-        let input = parse("620080001611562C8802118E34");
+        let input = parse("620080001611562C8802118E34").unwrap();
         assert_eq!(part1(&input), 12);
 
-        let input = parse("C0015000016115A2E0802F182340");
+        let input = parse("C0015000016115A2E0802F182340").unwrap();
         assert_eq!(part1(&input), 23);
 
-        let input = parse("A0016C880162017C3686B18A3D4780");
+        let input = parse("A0016C880162017C3686B18A3D4780").unwrap();
         assert_eq!(part1(&input), 31);
 
-        let input = parse("38006F45291200");
+        let input = parse("38006F45291200").unwrap();
         assert_eq!(part1(&input), 9);
     }
 
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("D2FE28").unwrap(),
+            Packet::Literal {
+                version: 6,
+                value: 2021
+            }
+        );
+
+        assert_eq!(
+            parse("38006F45291200").unwrap(),
+            Packet::Operator {
+                version: 1,
+                op: Op::Lt,
+                children: vec![
+                    Packet::Literal {
+                        version: 6,
+                        value: 10
+                    },
+                    Packet::Literal {
+                        version: 2,
+                        value: 20
+                    },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let e = parse("D2FG28").unwrap_err();
+        assert_eq!(e.to_string(), "column 4: expected a hex digit, found 'G'");
+
+        let packet = Packet::Literal {
+            version: 1,
+            value: usize::MAX,
+        };
+        let transmission = encode(&packet, LengthType::Packets).unwrap();
+        assert_eq!(parse(&transmission).unwrap(), packet);
+
+        // A literal with 17 groups of four bits.
+        let bits = format!("000100{}01111", "11111".repeat(16));
+        let e = parse(&hex(&bits)).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 2: expected a literal of at most 64 bits, found a wider literal"
+        );
+    }
+
+    // Hex transmission of the given bits padded to whole bytes.
+    fn hex(bits: &str) -> String {
+        let bits = format!("{:0<w$}", bits, w = bits.len().div_ceil(8) * 8);
+
+        (0..bits.len())
+            .step_by(4)
+            .map(|i| format!("{:X}", u8::from_str_radix(&bits[i..i + 4], 2).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        // truncated literal
+        let e = parse("D2FE2").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 6: expected 1 more bit, found end of line"
+        );
+
+        // truncated operator
+        let e = parse("  38006F452912").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 15: expected 1 more bit, found end of line"
+        );
+
+        // comparison with three sub-packets
+        let packet = "(> 1 2 3)".parse::<Packet>();
+        assert_eq!(
+            packet.unwrap_err().to_string(),
+            "column 9: expected 2 sub-packets for '>', found ')'"
+        );
+        let literal = |value| Packet::Literal { version: 0, value };
+        let operator = |op, children| Packet::Operator {
+            version: 0,
+            op,
+            children,
+        };
+
+        let packet = operator(Op::Gt, vec![literal(1), literal(2), literal(3)]);
+        let e = parse(&encode(&packet, LengthType::Bits).unwrap()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 1: expected 2 sub-packets for '>', found 3"
+        );
+
+        // empty operator
+        let packet = operator(Op::Sum, vec![operator(Op::Min, vec![])]);
+        let e = parse(&encode(&packet, LengthType::Packets).unwrap()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 5: expected sub-packets for 'min', found 0"
+        );
+
+        // a literal of 11 bits in sub-packets of 10 bits
+        let bits = format!("000000{}{:015b}{}", 0, 10, "00010000001");
+        let e = parse(&hex(&bits)).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 6: expected sub-packets of 10 bits, found 11 bits"
+        );
+
+        // padding with a set bit
+        let e = parse("D2FE29").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 6: expected zero padding, found a set bit"
+        );
+
+        // overflowing value
+        let packet = "(* 18446744073709551615 2)".parse::<Packet>().unwrap();
+        assert_eq!(packet.evaluate(), None);
+        let packet = "(+ 18446744073709551614 1)".parse::<Packet>().unwrap();
+        assert_eq!(packet.evaluate(), Some(usize::MAX));
+    }
+
+    // Hands out the bytes one at a time.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.split_first() {
+                Some((b, rest)) if !buf.is_empty() => {
+                    buf[0] = *b;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_decoder() {
+        let stream = "D2FE28\n38006F45291200\nC200B40A82 0000\n";
+        let packets = Decoder::new(Trickle(stream.as_bytes()))
+            .map(|packet| packet.unwrap().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(packets, vec!["2021", "(< 10 20)", "(+ 1 2)"]);
+
+        // Packets of a generated stream follow each other directly.
+        let input = std::fs::read_to_string("input/d16.txt").expect("file not found");
+        let packet = parse(&input).unwrap();
+        let stream = format!(
+            "{}{}",
+            encode(&packet, LengthType::Bits).unwrap(),
+            input.trim()
+        )
+        .repeat(3);
+        let packets = Decoder::new(stream.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(packets.len(), 6);
+        assert!(packets.iter().all(|p| *p == packet));
+    }
+
+    #[test]
+    fn test_decoder_error() {
+        let mut decoder = Decoder::new(Trickle(b"D2FE28 D2FX28"));
+
+        assert_eq!(decoder.next().unwrap().unwrap().to_string(), "2021");
+        assert_eq!(
+            decoder.next().unwrap().unwrap_err().to_string(),
+            "parse error in column 11: expected a hex digit, found 'X'"
+        );
+        assert!(decoder.next().is_none());
+
+        let mut decoder = Decoder::new("D2FE29D2FE28".as_bytes());
+        assert_eq!(
+            decoder.next().unwrap().unwrap_err().to_string(),
+            "parse error in column 6: expected zero padding, found a set bit"
+        );
+        assert!(decoder.next().is_none());
+
+        // Columns count the whitespace between packets.
+        let mut decoder = Decoder::new(Trickle(b"D2FE28\n  D2FE29"));
+        assert_eq!(decoder.next().unwrap().unwrap().to_string(), "2021");
+        assert_eq!(
+            decoder.next().unwrap().unwrap_err().to_string(),
+            "parse error in column 15: expected zero padding, found a set bit"
+        );
+
+        let mut decoder = Decoder::new(Trickle(b"D2FE28 38006F4529 "));
+        assert_eq!(decoder.next().unwrap().unwrap().to_string(), "2021");
+        assert_eq!(
+            decoder.next().unwrap().unwrap_err().to_string(),
+            "parse error in column 18: expected 4 more bits, found end of line"
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(parse("C200B40A82").unwrap().to_string(), "(+ 1 2)");
+        assert_eq!(parse("04005AC33890").unwrap().to_string(), "(* 6 9)");
+        assert_eq!(parse("880086C3E88112").unwrap().to_string(), "(min 7 8 9)");
+        assert_eq!(
+            parse("9C0141080250320F1802104A08").unwrap().to_string(),
+            "(= (+ 1 3) (* 2 2))"
+        );
+        assert_eq!(
+            parse("A0016C880162017C3686B18A3D4780").unwrap().to_string(),
+            "(+ (+ (+ 6 6 12 15 15)))"
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        let packet = "(+ 1 (* 2 3))".parse::<Packet>().unwrap();
+
+        assert_eq!(packet.to_string(), "(+ 1 (* 2 3))");
+        assert_eq!(packet.evaluate(), Some(7));
+
+        let packet = " (min  (max 4 5)(= 1 1) ) ".parse::<Packet>().unwrap();
+        assert_eq!(packet.to_string(), "(min (max 4 5) (= 1 1))");
+    }
+
+    #[test]
+    fn test_from_str_error() {
+        let e = "(- 1 2)".parse::<Packet>().unwrap_err();
+        assert_eq!(e.to_string(), "column 2: expected an operator, found '-'");
+
+        let e = "(+ 1 x)".parse::<Packet>().unwrap_err();
+        assert_eq!(e.to_string(), "column 6: expected a number, found 'x'");
+
+        let e = "(+ 1 (* 2 3)".parse::<Packet>().unwrap_err();
+        assert_eq!(e.to_string(), "column 13: expected ')', found end of line");
+
+        let e = "(+ 1 2))".parse::<Packet>().unwrap_err();
+        assert_eq!(e.to_string(), "column 8: expected end of line, found ')'");
+    }
+
+    #[test]
+    fn test_encode() {
+        let packet = parse("D2FE28").unwrap();
+        assert_eq!(encode(&packet, LengthType::Packets).unwrap(), "D2FE28");
+
+        let packet = parse("38006F45291200").unwrap();
+        assert_eq!(encode(&packet, LengthType::Bits).unwrap(), "38006F45291200");
+
+        let packet = parse("EE00D40C823060").unwrap();
+        assert_eq!(
+            encode(&packet, LengthType::Packets).unwrap(),
+            "EE00D40C823060"
+        );
+
+        let packet = "(+ 1 (* 2 3))".parse::<Packet>().unwrap();
+        assert_eq!(
+            parse(&encode(&packet, LengthType::Bits).unwrap())
+                .unwrap()
+                .evaluate(),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn test_encode_error() {
+        let literal = |version| Packet::Literal { version, value: 1 };
+
+        let packet = literal(8);
+        assert_eq!(
+            encode(&packet, LengthType::Bits),
+            Err(EncodeError::Version(8))
+        );
+
+        let packet = Packet::Operator {
+            version: 0,
+            op: Op::Sum,
+            children: vec![literal(0), literal(9)],
+        };
+        assert_eq!(
+            encode(&packet, LengthType::Packets),
+            Err(EncodeError::Version(9))
+        );
+
+        // 3000 sub-packets of 11 bits exceed both length fields.
+        let packet = Packet::Operator {
+            version: 0,
+            op: Op::Sum,
+            children: vec![literal(0); 3000],
+        };
+        for length_type in [LengthType::Bits, LengthType::Packets] {
+            assert_eq!(
+                encode(&packet, length_type),
+                Err(EncodeError::SubPackets(3000))
+            );
+        }
+        assert_eq!(
+            Error::from(EncodeError::SubPackets(3000)).to_string(),
+            "no solution: cannot encode packet, 3000 sub-packets exceed both length fields"
+        );
+    }
+
+    // A random packet tree of the given depth,
+    // using a xorshift generator for the choices.
+    fn random_packet(state: &mut u64, depth: usize) -> Packet {
+        let mut next = || {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            *state
+        };
+
+        let version = (next() % 8) as u8;
+
+        if depth == 0 || next() % 3 == 0 {
+            // Mix small values and values up to 64 bits.
+            let value = (next() >> (next() % 64)) as usize;
+            return Packet::Literal { version, value };
+        }
+
+        let op = Op::from_type_id([0, 1, 2, 3, 5, 6, 7][(next() % 7) as usize]);
+        let count = match op {
+            Op::Gt | Op::Lt | Op::Eq => 2,
+            _ => 1 + (next() % 4) as usize,
+        };
+        let children = (0..count)
+            .map(|_| random_packet(state, depth - 1))
+            .collect();
+
+        Packet::Operator {
+            version,
+            op,
+            children,
+        }
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let mut state = 0x2021_1216;
+
+        for _ in 0..500 {
+            let packet = random_packet(&mut state, 4);
+
+            for length_type in [LengthType::Bits, LengthType::Packets] {
+                let hex = encode(&packet, length_type).unwrap();
+                assert_eq!(parse(&hex).unwrap(), packet, "{}", hex);
+            }
+        }
+
+        let input = std::fs::read_to_string("input/d16.txt").expect("file not found");
+        let packet = parse(&input).unwrap();
+        assert_eq!(
+            parse(&encode(&packet, LengthType::Packets).unwrap()).unwrap(),
+            packet
+        );
+    }
+
     #[test]
     fn test_part2() {
-        let input = parse("C200B40A82");
-        assert_eq!(part2(&input), 3);
+        let input = parse("C200B40A82").unwrap();
+        assert_eq!(input.evaluate(), Some(3));
+
+        let input = parse("04005AC33890").unwrap();
+        assert_eq!(input.evaluate(), Some(54));
 
-        let input = parse("04005AC33890");
-        assert_eq!(part2(&input), 54);
+        let input = parse("880086C3E88112").unwrap();
+        assert_eq!(input.evaluate(), Some(7));
 
-        let input = parse("880086C3E88112");
-        assert_eq!(part2(&input), 7);
+        let input = parse("CE00C43D881120").unwrap();
+        assert_eq!(input.evaluate(), Some(9));
 
-        let input = parse("CE00C43D881120");
-        assert_eq!(part2(&input), 9);
+        let input = parse("D8005AC2A8F0").unwrap();
+        assert_eq!(input.evaluate(), Some(1));
 
-        let input = parse("D8005AC2A8F0");
-        assert_eq!(part2(&input), 1);
+        let input = parse("F600BC2D8F").unwrap();
+        assert_eq!(input.evaluate(), Some(0));
 
-        let input = parse("F600BC2D8F");
-        assert_eq!(part2(&input), 0);
+        let input = parse("9C005AC2F8F0").unwrap();
+        assert_eq!(input.evaluate(), Some(0));
+
+        let input = parse("9C0141080250320F1802104A08").unwrap();
+        assert_eq!(input.evaluate(), Some(1));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let packet = "(* 18446744073709551615 2)".parse::<Packet>().unwrap();
+        let input = encode(&packet, LengthType::Bits).unwrap();
 
-        let input = parse("9C005AC2F8F0");
-        assert_eq!(part2(&input), 0);
+        let e = Day16::parse(&input).err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "no solution: the value of the transmission overflows"
+        );
 
-        let input = parse("9C0141080250320F1802104A08");
-        assert_eq!(part2(&input), 1);
+        let transmission = Day16::parse("9C0141080250320F1802104A08").unwrap();
+        assert_eq!(Day16::part1(&transmission), 20);
+        assert_eq!(Day16::part2(&transmission), 1);
     }
 
     #[bench]
     fn bench_parse(b: &mut Bencher) {
         let input = std::fs::read_to_string("input/d16.txt").expect("file not found");
 
-        b.iter(|| parse(input.as_str()));
+        b.iter(|| parse(input.as_str()).unwrap());
     }
 
     #[bench]
     fn bench_part1(b: &mut Bencher) {
         let input = std::fs::read_to_string("input/d16.txt").expect("file not found");
-        let line = parse(input.as_str());
-        b.iter(|| part1(&line));
+        let packet = parse(input.as_str()).unwrap();
+        b.iter(|| part1(&packet));
     }
 
     #[bench]
     fn bench_part2(b: &mut Bencher) {
         let input = std::fs::read_to_string("input/d16.txt").expect("file not found");
-        let line = parse(input.as_str());
-        b.iter(|| part2(&line));
+        let packet = parse(input.as_str()).unwrap();
+        b.iter(|| packet.evaluate());
     }
 }
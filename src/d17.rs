@@ -1,5 +1,26 @@
-pub fn run(line: &str) -> (usize, usize) {
-    simulate(parse(line))
+use crate::{
+    error::{parse_number, split_once, Error, ParseError},
+    solution::Solution,
+};
+
+type Target = ((i32, i32), (i32, i32));
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = Target;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(parse(input.trim_end()).map_err(|e| e.at_line(1))?)
+    }
+
+    fn part1(target: &Self::Input) -> usize {
+        simulate(*target).0
+    }
+
+    fn part2(target: &Self::Input) -> usize {
+        simulate(*target).1
+    }
 }
 
 fn simulate(target @ ((_, x_max), (y_min, _)): ((i32, i32), (i32, i32))) -> (usize, usize) {
@@ -46,18 +67,18 @@ fn fire(
     }
 }
 
-fn parse(line: &str) -> ((i32, i32), (i32, i32)) {
-    let (x, y) = line[13..].split_once(", ").unwrap();
-    let x_range = x[2..]
-        .split_once("..")
-        .map(|(l, h)| (l.parse::<i32>().unwrap(), h.parse::<i32>().unwrap()))
-        .unwrap();
-    let y_range = y[2..]
-        .split_once("..")
-        .map(|(l, h)| (l.parse::<i32>().unwrap(), h.parse::<i32>().unwrap()))
-        .unwrap();
-
-    (x_range, y_range)
+fn parse(line: &str) -> Result<Target, ParseError> {
+    let ranges = line
+        .strip_prefix("target area: x=")
+        .ok_or_else(|| ParseError::at(line, line, "'target area: x='"))?;
+    let (x, y) = split_once(line, ranges, ", y=")?;
+
+    let range = |range| {
+        let (low, high) = split_once(line, range, "..")?;
+        Ok((parse_number(line, low)?, parse_number(line, high)?))
+    };
+
+    Ok((range(x)?, range(y)?))
 }
 
 #[cfg(test)]
@@ -70,39 +91,57 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let target_area = parse(INPUT);
+        let target_area = parse(INPUT).unwrap();
 
         assert_eq!(simulate(target_area).0, 45);
     }
 
     #[test]
     fn test_part2() {
-        let target_area = parse(INPUT);
+        let target_area = parse(INPUT).unwrap();
 
         assert_eq!(simulate(target_area).1, 112);
     }
 
     #[test]
     fn test_part1_input() {
-        let target_area = parse(INPUT1);
+        let target_area = parse(INPUT1).unwrap();
 
         assert_eq!(simulate(target_area).0, 5995);
     }
 
     #[test]
     fn test_part2_input() {
-        let target_area = parse(INPUT1);
+        let target_area = parse(INPUT1).unwrap();
 
         assert_eq!(simulate(target_area).1, 3202);
     }
 
     #[test]
     fn test_paul() {
-        let target_area = parse(INPUT2);
+        let target_area = parse(INPUT2).unwrap();
 
         let (peak, vels) = simulate(target_area);
 
         assert_eq!(peak, 12561);
         assert_eq!(vels, 3785);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let e = parse("target area: x=20..30, y=-10..x").unwrap_err();
+        assert_eq!(e.to_string(), "column 31: expected a number, found 'x'");
+
+        let e = parse("target area: x=20..30 y=-10..-5").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 16: expected ', y=', found '20..30 y=-10..-5'"
+        );
+
+        let e = Day17::parse("x\n").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "parse error in line 1, column 1: expected 'target area: x=', found 'x'"
+        );
+    }
 }
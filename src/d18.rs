@@ -1,11 +1,54 @@
 use std::str::FromStr;
 
-pub fn run(trees: Vec<Tree>) -> (usize, usize) {
-    (part1(trees.clone()), part2(trees))
+use crate::{
+    error::{Error, ParseError},
+    parallel,
+    solution::{parse_lines, Solution},
+};
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = Vec<Tree>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let trees = parse_lines(input)?;
+        if trees.is_empty() {
+            return Err(ParseError::at(input, input, "a snailfish number")
+                .at_line(1)
+                .into());
+        }
+
+        Ok(trees)
+    }
+
+    fn part1(trees: &Self::Input) -> usize {
+        part1(trees.iter().map(Flat::from).collect())
+    }
+
+    fn part2(trees: &Self::Input) -> usize {
+        part2(trees.iter().map(Flat::from).collect())
+    }
 }
 
-fn part1(trees: Vec<Tree>) -> usize {
-    trees
+// The operations on snailfish numbers, implemented by the
+// recursive `Tree` and the flat, depth-annotated `Flat`.
+trait Snailfish: Clone {
+    fn add(&mut self, other: Self);
+
+    fn reduce(&mut self) {
+        while self.explode() || self.split() {}
+    }
+
+    fn explode(&mut self) -> bool;
+
+    fn split(&mut self) -> bool;
+
+    fn magnitude(&self) -> u32;
+}
+
+fn part1<N: Snailfish>(numbers: Vec<N>) -> usize {
+    numbers
         .into_iter()
         .reduce(|mut t1, t2| {
             t1.add(t2);
@@ -16,24 +59,27 @@ fn part1(trees: Vec<Tree>) -> usize {
         .magnitude() as usize
 }
 
-fn part2(trees: Vec<Tree>) -> usize {
-    trees
-        .iter()
-        .map(|left| {
-            trees
-                .iter()
-                .cloned()
-                .map(|right| {
-                    let mut l = left.clone();
-                    l.add(right);
-                    l.reduce();
-                    l.magnitude()
-                })
-                .max()
-                .unwrap()
-        })
-        .max()
-        .unwrap() as usize
+fn part2<N: Snailfish + Sync>(numbers: Vec<N>) -> usize {
+    part2_threads(numbers, parallel::threads())
+}
+
+fn part2_threads<N: Snailfish + Sync>(numbers: Vec<N>, threads: usize) -> usize {
+    parallel::map(numbers.len(), threads, |i| {
+        numbers
+            .iter()
+            .cloned()
+            .map(|right| {
+                let mut l = numbers[i].clone();
+                l.add(right);
+                l.reduce();
+                l.magnitude()
+            })
+            .max()
+            .unwrap()
+    })
+    .into_iter()
+    .max()
+    .unwrap() as usize
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -42,12 +88,37 @@ pub enum Tree {
     Pair(Box<(Tree, Tree)>),
 }
 
-#[derive(Clone, Copy)]
-enum Direction {
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
     Left,
     Right,
 }
 
+// A single action of the reduction, with the
+// path to the node it applied to and the result.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReductionStep {
+    Explode {
+        path: Vec<Direction>,
+        pair: (u8, u8),
+        result: Tree,
+    },
+    Split {
+        path: Vec<Direction>,
+        value: u8,
+        result: Tree,
+    },
+}
+
+impl std::fmt::Display for ReductionStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Explode { result, .. } => write!(f, "after explode:  {}", result),
+            Self::Split { result, .. } => write!(f, "after split:    {}", result),
+        }
+    }
+}
+
 impl std::fmt::Display for Tree {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -66,6 +137,79 @@ impl Tree {
         s.parse::<Self>().unwrap()
     }
 
+    fn insert(&mut self, value: u8, d: Direction) {
+        match (self, d) {
+            (Tree::Reg(v), _) => *v += value,
+            (Tree::Pair(pair), Direction::Left) => pair.0.insert(value, d),
+            (Tree::Pair(pair), Direction::Right) => pair.1.insert(value, d),
+        }
+    }
+
+    // Like `reduce`, but records every explode and split on the way.
+    pub fn trace(&mut self) -> Vec<ReductionStep> {
+        let mut steps = vec![];
+
+        loop {
+            let exploding = self.find(&|tree, depth| depth == 4 && matches!(tree, Tree::Pair(_)));
+            if let Some((path, Tree::Pair(pair))) = exploding {
+                let pair = match **pair {
+                    (Tree::Reg(l), Tree::Reg(r)) => (l, r),
+                    _ => unreachable!(),
+                };
+                self.explode();
+                steps.push(ReductionStep::Explode {
+                    path,
+                    pair,
+                    result: self.clone(),
+                });
+                continue;
+            }
+
+            let splitting = self.find(&|tree, _| matches!(tree, Tree::Reg(v) if *v > 9));
+            if let Some((path, &Tree::Reg(value))) = splitting {
+                self.split();
+                steps.push(ReductionStep::Split {
+                    path,
+                    value,
+                    result: self.clone(),
+                });
+                continue;
+            }
+
+            return steps;
+        }
+    }
+
+    // The leftmost node matching `f`, given the node and its depth.
+    fn find(&self, f: &impl Fn(&Tree, usize) -> bool) -> Option<(Vec<Direction>, &Tree)> {
+        let mut path = vec![];
+        return find(self, &mut path, f).map(|tree| (path, tree));
+
+        fn find<'a>(
+            tree: &'a Tree,
+            path: &mut Vec<Direction>,
+            f: &impl Fn(&Tree, usize) -> bool,
+        ) -> Option<&'a Tree> {
+            if f(tree, path.len()) {
+                return Some(tree);
+            }
+
+            if let Tree::Pair(pair) = tree {
+                for (d, child) in [(Direction::Left, &pair.0), (Direction::Right, &pair.1)] {
+                    path.push(d);
+                    if let Some(tree) = find(child, path, f) {
+                        return Some(tree);
+                    }
+                    path.pop();
+                }
+            }
+
+            None
+        }
+    }
+}
+
+impl Snailfish for Tree {
     fn magnitude(&self) -> u32 {
         match self {
             Self::Reg(v) => *v as u32,
@@ -79,18 +223,6 @@ impl Tree {
         *self = num;
     }
 
-    fn reduce(&mut self) {
-        while self.explode() || self.split() {}
-    }
-
-    fn insert(&mut self, value: u8, d: Direction) {
-        match (self, d) {
-            (Tree::Reg(v), _) => *v += value,
-            (Tree::Pair(pair), Direction::Left) => pair.0.insert(value, d),
-            (Tree::Pair(pair), Direction::Right) => pair.1.insert(value, d),
-        }
-    }
-
     fn explode(&mut self) -> bool {
         return explode(self, 0).is_some();
 
@@ -148,33 +280,185 @@ impl<T: Into<Self>, U: Into<Self>> From<(T, U)> for Tree {
     }
 }
 
+// The regular numbers from left to right,
+// each with the number of pairs containing it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Flat(Vec<(u8, u8)>);
+
+impl Snailfish for Flat {
+    fn add(&mut self, other: Self) {
+        self.0.extend(other.0);
+        self.0.iter_mut().for_each(|(_, depth)| *depth += 1);
+    }
+
+    fn explode(&mut self) -> bool {
+        // In a reduced number, the leftmost regular number nested
+        // within four pairs is the left value of the exploding pair.
+        let i = match self.0.iter().position(|(_, depth)| *depth > 4) {
+            Some(i) => i,
+            None => return false,
+        };
+
+        let (left, depth) = self.0[i];
+        let (right, _) = self.0.remove(i + 1);
+        self.0[i] = (0, depth - 1);
+
+        if i > 0 {
+            self.0[i - 1].0 += left;
+        }
+        if let Some((v, _)) = self.0.get_mut(i + 1) {
+            *v += right;
+        }
+
+        true
+    }
+
+    fn split(&mut self) -> bool {
+        let i = match self.0.iter().position(|(v, _)| *v > 9) {
+            Some(i) => i,
+            None => return false,
+        };
+
+        let (v, depth) = self.0[i];
+        self.0[i] = (v / 2, depth + 1);
+        self.0.insert(i + 1, (v.div_ceil(2), depth + 1));
+
+        true
+    }
+
+    fn magnitude(&self) -> u32 {
+        // Combine neighbours on the same depth until a
+        // single value remains, similar to a shift-reduce
+        // parser on the depth-first order.
+        let mut stack: Vec<(u32, u8)> = vec![];
+
+        for (v, depth) in &self.0 {
+            let mut top = (*v as u32, *depth);
+
+            while let Some(&(left, depth)) = stack.last() {
+                if depth != top.1 {
+                    break;
+                }
+                top = (3 * left + 2 * top.0, depth - 1);
+                stack.pop();
+            }
+
+            stack.push(top);
+        }
+
+        stack[0].0
+    }
+}
+
+impl From<&Tree> for Flat {
+    fn from(tree: &Tree) -> Self {
+        let mut flat = vec![];
+        let mut stack = vec![(tree, 0)];
+
+        while let Some((tree, depth)) = stack.pop() {
+            match tree {
+                Tree::Reg(v) => flat.push((*v, depth)),
+                Tree::Pair(pair) => {
+                    stack.push((&pair.1, depth + 1));
+                    stack.push((&pair.0, depth + 1));
+                }
+            }
+        }
+
+        Self(flat)
+    }
+}
+
+impl From<&Flat> for Tree {
+    fn from(flat: &Flat) -> Self {
+        return tree(&mut flat.0.iter().peekable(), 0);
+
+        fn tree<'a>(
+            values: &mut std::iter::Peekable<impl Iterator<Item = &'a (u8, u8)>>,
+            depth: u8,
+        ) -> Tree {
+            match values.peek() {
+                Some((v, d)) if *d == depth => {
+                    values.next();
+                    Tree::Reg(*v)
+                }
+                _ => {
+                    let left = tree(values, depth + 1);
+                    let right = tree(values, depth + 1);
+                    Tree::of((left, right))
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Flat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Tree::of(self))
+    }
+}
+
 impl FromStr for Tree {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (node, _) = parse(s.as_bytes());
-        Ok(node)
+        let (node, rem) = parse(s, s.as_bytes(), 0)?;
+        match rem {
+            [] => Ok(node),
+            _ => Err(error(s, rem, "end of line")),
+        }
     }
 }
 
-fn parse(bytes: &[u8]) -> (Tree, &[u8]) {
-    match bytes[0] {
-        b'[' => {
-            let (lhs, rem) = parse(&bytes[1..]);
-            let (rhs, rem) = parse(&rem[1..]); // skip comma
-            (Tree::Pair(Box::new((lhs, rhs))), &rem[1..]) // skip ]
+// Only accepts reduced numbers: no pair is nested
+// inside four pairs and regular numbers are digits.
+fn parse<'a>(s: &str, bytes: &'a [u8], depth: usize) -> Result<(Tree, &'a [u8]), ParseError> {
+    match bytes.first() {
+        Some(b'[') if depth == 4 => Err(error(s, bytes, "a digit, pairs nest at most 4 deep")),
+        Some(b'[') => {
+            let (lhs, rem) = parse(s, &bytes[1..], depth + 1)?;
+            let rem = expect(s, rem, b',')?;
+            let (rhs, rem) = parse(s, rem, depth + 1)?;
+            let rem = expect(s, rem, b']')?;
+            Ok((Tree::Pair(Box::new((lhs, rhs))), rem))
+        }
+        Some(b'0'..=b'9') => {
+            let len = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+            if len > 1 {
+                let offset = s.len() - bytes.len();
+                return Err(ParseError::at(
+                    s,
+                    &s[offset..offset + len],
+                    "a regular number from 0 to 9",
+                ));
+            }
+            Ok((Tree::Reg(bytes[0] - b'0'), &bytes[1..]))
         }
-        b'0'..=b'9' => (Tree::Reg(bytes[0] - b'0'), &bytes[1..]),
-        c => panic!("unexpected: '{}'", c),
+        _ => Err(error(s, bytes, "'[' or a digit")),
     }
 }
 
+fn expect<'a>(s: &str, bytes: &'a [u8], b: u8) -> Result<&'a [u8], ParseError> {
+    match bytes.first() {
+        Some(c) if *c == b => Ok(&bytes[1..]),
+        _ => Err(error(s, bytes, format!("'{}'", b as char))),
+    }
+}
+
+fn error(s: &str, rem: &[u8], expected: impl Into<String>) -> ParseError {
+    let offset = s.len() - rem.len();
+    let len = s[offset..].chars().next().map_or(0, char::len_utf8);
+
+    ParseError::at(s, &s[offset..offset + len], expected)
+}
+
 #[cfg(test)]
 mod tests {
 
     use test::Bencher;
 
     use super::*;
+    use crate::{answers::expected, solution::Part};
 
     const INPUT: &str = r#"[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
                            [[[5,[2,8]],4],[5,[[9,9],0]]]
@@ -211,6 +495,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_error() {
+        let e = "[1,2".parse::<Tree>().err().unwrap();
+        assert_eq!(e.to_string(), "column 5: expected ']', found end of line");
+
+        let e = "[1;2]".parse::<Tree>().err().unwrap();
+        assert_eq!(e.to_string(), "column 3: expected ',', found ';'");
+
+        let e = "[[1,2],x]".parse::<Tree>().err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 8: expected '[' or a digit, found 'x'"
+        );
+
+        let e = "[[1,2],3]]".parse::<Tree>().err().unwrap();
+        assert_eq!(e.to_string(), "column 10: expected end of line, found ']'");
+
+        let e = "[1,2] [3,4]".parse::<Tree>().err().unwrap();
+        assert_eq!(e.to_string(), "column 6: expected end of line, found ' '");
+
+        let e = "[1[2,3]]".parse::<Tree>().err().unwrap();
+        assert_eq!(e.to_string(), "column 3: expected ',', found '['");
+
+        let e = "[[1,2],,3]".parse::<Tree>().err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 8: expected '[' or a digit, found ','"
+        );
+
+        let e = "[1,[15,2]]".parse::<Tree>().err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 5: expected a regular number from 0 to 9, found '15'"
+        );
+
+        let e = "[[[[[9,8],1],2],3],4]".parse::<Tree>().err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 5: expected a digit, pairs nest at most 4 deep, found '['"
+        );
+
+        assert!("[[[[9,8],1],2],3]".parse::<Tree>().is_ok());
+
+        let e = Day18::parse("").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "parse error in line 1, column 1: expected a snailfish number, found end of line"
+        );
+    }
+
+    #[test]
+    fn test_flat() {
+        let tree = Tree::from("[[1,2],[[3,4],5]]");
+        let flat = Flat::from(&tree);
+
+        assert_eq!(flat, Flat(vec![(1, 2), (2, 2), (3, 3), (4, 3), (5, 2)]));
+        assert_eq!(Tree::of(&flat), tree);
+
+        for line in INPUT.lines() {
+            let tree = Tree::from(line.trim());
+            assert_eq!(Tree::of(&Flat::from(&tree)), tree);
+        }
+    }
+
     #[test]
     fn test_part1() {
         let trees = INPUT
@@ -218,7 +566,10 @@ mod tests {
             .map(|line| line.trim().parse::<Tree>().unwrap())
             .collect::<Vec<Tree>>();
 
+        let flat = trees.iter().map(Flat::from).collect::<Vec<_>>();
+
         assert_eq!(part1(trees), 4140);
+        assert_eq!(part1(flat), 4140);
     }
 
     #[test]
@@ -228,7 +579,12 @@ mod tests {
             .map(|line| line.trim().parse::<Tree>().unwrap())
             .collect::<Vec<Tree>>();
 
+        let flat = trees.iter().map(Flat::from).collect::<Vec<_>>();
+
+        assert_eq!(part2_threads(trees.clone(), 4), 3993);
+        assert_eq!(part2_threads(flat.clone(), 4), 3993);
         assert_eq!(part2(trees), 3993);
+        assert_eq!(part2(flat), 3993);
     }
 
     #[test]
@@ -245,6 +601,18 @@ mod tests {
             Tree::of(((6, (5, (7, 0))), 3)),
         ];
 
+        assert_eq!(
+            input
+                .iter()
+                .map(Flat::from)
+                .map(|mut flat| {
+                    flat.explode();
+                    flat
+                })
+                .collect::<Vec<_>>(),
+            expected.iter().map(Flat::from).collect::<Vec<_>>()
+        );
+
         assert_eq!(
             input
                 .into_iter()
@@ -271,6 +639,18 @@ mod tests {
             Tree::of(((((0, 7), 4), ((7, 8), (0, (6, 7)))), (1, 1))),
         ];
 
+        assert_eq!(
+            actual
+                .iter()
+                .map(Flat::from)
+                .map(|mut flat| {
+                    flat.split();
+                    flat
+                })
+                .collect::<Vec<_>>(),
+            expected.iter().map(Flat::from).collect::<Vec<_>>()
+        );
+
         assert_eq!(
             actual
                 .into_iter()
@@ -283,10 +663,71 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_trace() {
+        use Direction::*;
+
+        let mut tree = Tree::from("[[[[4,3],4],4],[7,[[8,4],9]]]");
+        tree.add(Tree::from("[1,1]"));
+
+        let steps = tree.trace();
+
+        assert_eq!(
+            steps,
+            vec![
+                ReductionStep::Explode {
+                    path: vec![Left, Left, Left, Left],
+                    pair: (4, 3),
+                    result: Tree::of(((((0, 7), 4), (7, ((8, 4), 9))), (1, 1))),
+                },
+                ReductionStep::Explode {
+                    path: vec![Left, Right, Right, Left],
+                    pair: (8, 4),
+                    result: Tree::of(((((0, 7), 4), (15, (0, 13))), (1, 1))),
+                },
+                ReductionStep::Split {
+                    path: vec![Left, Right, Left],
+                    value: 15,
+                    result: Tree::of(((((0, 7), 4), ((7, 8), (0, 13))), (1, 1))),
+                },
+                ReductionStep::Split {
+                    path: vec![Left, Right, Right, Right],
+                    value: 13,
+                    result: Tree::of(((((0, 7), 4), ((7, 8), (0, (6, 7)))), (1, 1))),
+                },
+                ReductionStep::Explode {
+                    path: vec![Left, Right, Right, Right],
+                    pair: (6, 7),
+                    result: Tree::from("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"),
+                },
+            ]
+        );
+        assert_eq!(tree, Tree::from("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"));
+
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| step.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "after explode:  [[[[0,7],4],[7,[[8,4],9]]],[1,1]]
+after explode:  [[[[0,7],4],[15,[0,13]]],[1,1]]
+after split:    [[[[0,7],4],[[7,8],[0,13]]],[1,1]]
+after split:    [[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]
+after explode:  [[[[0,7],4],[[7,8],[6,0]]],[8,1]]"
+        );
+
+        assert!(tree.trace().is_empty());
+    }
+
     #[test]
     fn test_magnitude() {
         assert_eq!(Tree::of((9, 1)).magnitude(), 29);
         assert_eq!(Tree::of(((9, 1), (1, 9))).magnitude(), 129);
+
+        let tree = Tree::from("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]");
+        assert_eq!(tree.magnitude(), 3488);
+        assert_eq!(Flat::from(&tree).magnitude(), 3488);
     }
 
     #[test]
@@ -304,6 +745,16 @@ mod tests {
             Tree::from("[[[[4,2],2],6],[8,7]]"),
         ];
 
+        let flat = trees
+            .iter()
+            .map(Flat::from)
+            .reduce(|mut f1, f2| {
+                f1.add(f2);
+                f1.reduce();
+                f1
+            })
+            .unwrap();
+
         assert_eq!(
             trees
                 .into_iter()
@@ -315,6 +766,11 @@ mod tests {
                 .unwrap(),
             Tree::from("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]")
         );
+
+        assert_eq!(
+            flat.to_string(),
+            "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]"
+        );
     }
 
     #[bench]
@@ -322,20 +778,64 @@ mod tests {
         let input = std::fs::read_to_string("input/d18.txt")
             .expect("file not found")
             .lines()
-            .map(|line| line.parse::<Tree>().unwrap())
+            .map(|line| Flat::from(&line.parse::<Tree>().unwrap()))
             .collect::<Vec<_>>();
 
-        b.iter(|| assert_eq!(part1(input.clone()), 3987));
+        let expected = expected(18, Part::One);
+
+        b.iter(|| assert_eq!(part1(input.clone()), expected));
     }
 
     #[bench]
     fn bench_part2(b: &mut Bencher) {
+        let input = std::fs::read_to_string("input/d18.txt")
+            .expect("file not found")
+            .lines()
+            .map(|line| Flat::from(&line.parse::<Tree>().unwrap()))
+            .collect::<Vec<_>>();
+
+        let expected = expected(18, Part::Two);
+
+        b.iter(|| assert_eq!(part2(input.clone()), expected));
+    }
+
+    #[bench]
+    fn bench_part1_tree(b: &mut Bencher) {
+        let input = std::fs::read_to_string("input/d18.txt")
+            .expect("file not found")
+            .lines()
+            .map(|line| line.parse::<Tree>().unwrap())
+            .collect::<Vec<_>>();
+
+        let expected = expected(18, Part::One);
+
+        b.iter(|| assert_eq!(part1(input.clone()), expected));
+    }
+
+    #[bench]
+    fn bench_part2_tree(b: &mut Bencher) {
         let input = std::fs::read_to_string("input/d18.txt")
             .expect("file not found")
             .lines()
             .map(|line| line.parse::<Tree>().unwrap())
             .collect::<Vec<_>>();
 
-        b.iter(|| assert_eq!(part2(input.clone()), 4500));
+        let expected = expected(18, Part::Two);
+
+        b.iter(|| assert_eq!(part2(input.clone()), expected));
+    }
+
+    #[bench]
+    fn bench_part2_parallel(b: &mut Bencher) {
+        let input = std::fs::read_to_string("input/d18.txt")
+            .expect("file not found")
+            .lines()
+            .map(|line| Flat::from(&line.parse::<Tree>().unwrap()))
+            .collect::<Vec<_>>();
+
+        let expected = expected(18, Part::Two);
+        let threads = std::thread::available_parallelism().unwrap().get();
+
+        b.iter(|| assert_eq!(part2_threads(input.clone(), threads), expected));
     }
 }
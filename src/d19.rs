@@ -1,304 +1,673 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    io::{self, Write},
     ops::{Add, Sub},
     str::FromStr,
 };
 
-use graph::prelude::*;
+use crate::{
+    error::{parse_number, split_once, Error, ParseError},
+    parallel,
+    solution::Solution,
+};
 
-pub fn run(input: &str) -> (usize, usize) {
-    let mut cubes = input
-        .split("\n\n")
-        .map(|scanner| scanner.parse::<Cube>().unwrap())
-        .collect::<Vec<_>>();
+pub struct Day19;
+
+// Scanners overlap if they detect at least this many common beacons.
+pub const THRESHOLD: usize = 12;
+
+impl Solution for Day19 {
+    type Input = Map;
 
-    compute(&mut cubes)
+    const SOLVES_IN_PARSE: bool = true;
+
+    // Assembles the map up front, as scanners that
+    // cannot be placed make the input unsolvable.
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(reconstruct(&parse(input)?, THRESHOLD)?)
+    }
+
+    fn part1(map: &Self::Input) -> usize {
+        map.beacons.len()
+    }
+
+    fn part2(map: &Self::Input) -> usize {
+        map.max_distance() as usize
+    }
 }
 
-fn compute(cubes: &mut [Cube]) -> (usize, usize) {
-    let mut rotations = HashMap::new();
+// A scanner and the transformation from its
+// coordinates into the coordinates of scanner 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scanner<const N: usize = 3> {
+    pub id: u32,
+    pub transform: Transform<N>,
+}
 
-    // find rotation and translation information for overlapping cubes
-    for id_left in 0..cubes.len() {
-        for id_right in (id_left + 1)..cubes.len() {
-            let matches = cubes[id_left].overlap(&cubes[id_right]);
+// All scanners and the distinct beacons they
+// detect, in the coordinates of scanner 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Map<const N: usize = 3> {
+    pub scanners: Vec<Scanner<N>>,
+    pub beacons: Vec<Vector<N>>,
+}
 
-            if !matches.is_empty() {
-                let (diff_left, diff_right) = matches
-                    .array_windows()
-                    .map(|[(l0, r0), (l1, r1)]| (*l0 - *l1, *r0 - *r1))
-                    .find(|(l_diff, _)| l_diff.x != l_diff.y && l_diff.y != l_diff.z)
-                    .unwrap();
+impl<const N: usize> Scanner<N> {
+    pub fn position(&self) -> Vector<N> {
+        self.transform.translation
+    }
 
-                let (rotation, sign) = diff_right.mapping(&diff_left);
-                let scanner = Vector::center(&matches[0].0, &matches[0].1.rotate(rotation, sign));
-                rotations.insert((id_left, id_right), (scanner, rotation, sign));
+    // The directions of the scanner's axes.
+    pub fn axes(&self) -> [Vector<N>; N] {
+        Vector::axes().map(|axis| self.transform.turn(axis))
+    }
+}
 
-                let (rotation, sign) = diff_left.mapping(&diff_right);
-                let scanner = Vector::center(&matches[0].1, &matches[0].0.rotate(rotation, sign));
-                rotations.insert((id_right, id_left), (scanner, rotation, sign));
-            }
+impl<const N: usize> Map<N> {
+    // The largest Manhattan distance between any two scanners.
+    pub fn max_distance(&self) -> u32 {
+        self.scanners
+            .iter()
+            .flat_map(|s1| {
+                self.scanners
+                    .iter()
+                    .map(|s2| s1.position().manhattan_distance(&s2.position()))
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    // One row per scanner and beacon. Scanner axes are given as the
+    // axis of scanner 0 they point along, e.g. `-y`.
+    pub fn write_csv(&self, mut w: impl Write) -> io::Result<()> {
+        let names = (0..N).map(axis_name).collect::<Vec<_>>();
+        writeln!(
+            w,
+            "kind,id,{},{}",
+            names.join(","),
+            names
+                .iter()
+                .map(|name| format!("{}_axis", name))
+                .collect::<Vec<_>>()
+                .join(",")
+        )?;
+
+        for scanner in &self.scanners {
+            let axes = scanner.axes().map(|axis| axis.direction());
+            writeln!(
+                w,
+                "scanner,{},{},{}",
+                scanner.id,
+                scanner.position(),
+                axes.join(",")
+            )?;
+        }
+
+        for p in &self.beacons {
+            writeln!(w, "beacon,,{}{}", p, ",".repeat(N))?;
         }
+
+        Ok(())
     }
 
-    // find paths from each cube to cube 0 and transform according to rotations
-    let g: UndirectedCsrGraph<usize> = GraphBuilder::new()
-        .edges(rotations.keys().copied().collect::<Vec<_>>())
-        .build();
+    // An ASCII PLY point cloud with beacons in white and scanners in red.
+    pub fn write_ply(&self, mut w: impl Write) -> io::Result<()> {
+        let scanners = self
+            .scanners
+            .iter()
+            .map(|scanner| scanner.position().xyz())
+            .collect::<io::Result<Vec<_>>>()?;
+        let beacons = self
+            .beacons
+            .iter()
+            .map(Vector::xyz)
+            .collect::<io::Result<Vec<_>>>()?;
+
+        writeln!(w, "ply")?;
+        writeln!(w, "format ascii 1.0")?;
+        writeln!(
+            w,
+            "element vertex {}",
+            self.scanners.len() + self.beacons.len()
+        )?;
+        for property in ["int x", "int y", "int z"] {
+            writeln!(w, "property {}", property)?;
+        }
+        for property in ["uchar red", "uchar green", "uchar blue"] {
+            writeln!(w, "property {}", property)?;
+        }
+        writeln!(w, "end_header")?;
+
+        for [x, y, z] in scanners {
+            writeln!(w, "{} {} {} 255 0 0", x, y, z)?;
+        }
+        for [x, y, z] in beacons {
+            writeln!(w, "{} {} {} 255 255 255", x, y, z)?;
+        }
+
+        Ok(())
+    }
 
-    let mut beacons = cubes[0].points.iter().copied().collect::<HashSet<_>>();
-    let mut scanners = vec![Vector::default(); cubes.len()];
+    // The beacons as XYZ point cloud, one `x y z` line per beacon.
+    pub fn write_xyz(&self, mut w: impl Write) -> io::Result<()> {
+        self.beacons.iter().try_for_each(|p| {
+            let [x, y, z] = p.xyz()?;
+            writeln!(w, "{} {} {}", x, y, z)
+        })
+    }
+}
 
-    for cube_id in 1..cubes.len() {
-        let path = dfs(&g, cube_id);
+fn parse(input: &str) -> Result<Vec<Cube>, ParseError> {
+    parse_scanners(input)
+}
 
-        let mut scanner_base: Option<Vector> = None;
+// Scanners of any dimension, with one comma separated point per line.
+pub fn parse_scanners<const N: usize>(input: &str) -> Result<Vec<Cube<N>>, ParseError> {
+    let mut lines = 0;
 
-        for (from, to) in path.iter().rev() {
-            let (center, rotation, sign) = rotations.get(&(*from, *to)).unwrap();
+    input
+        .split("\n\n")
+        .map(|scanner| {
+            let cube = scanner.parse::<Cube<N>>().map_err(|e| e.after_lines(lines));
+            lines += scanner.split('\n').count() + 1;
+            cube
+        })
+        .collect()
+}
 
-            cubes[cube_id].points.iter_mut().for_each(|point| {
-                let rotated = point.rotate(*rotation, *sign);
-                let translated = *center + rotated;
+// Scanners that do not share enough beacons with the others to be placed,
+// grouped by the overlaps among them. Each group could be assembled
+// on its own, but has no chain of overlapping scanners to scanner 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unplaced {
+    groups: Vec<Vec<u32>>,
+    threshold: usize,
+}
 
-                *point = translated;
-            });
+impl std::fmt::Display for Unplaced {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ids = |group: &[u32]| {
+            group
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
 
-            scanner_base = if let Some(c) = scanner_base {
-                let rotated = c.rotate(*rotation, *sign);
-                let translated = *center + rotated;
-                Some(translated)
-            } else {
-                Some(*center)
-            };
+        match &self.groups[..] {
+            [group] if group.len() == 1 => write!(f, "cannot place scanner {}", ids(group))?,
+            [group] => write!(f, "cannot place scanners {}", ids(group))?,
+            groups => write!(
+                f,
+                "cannot place {} groups of scanners {}",
+                groups.len(),
+                groups
+                    .iter()
+                    .map(|group| format!("{{{}}}", ids(group)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?,
         }
+        write!(
+            f,
+            ", no chain of scanners sharing at least {} beacons leads to scanner 0",
+            self.threshold
+        )
+    }
+}
 
-        scanners[cube_id] = scanner_base.unwrap();
+impl std::error::Error for Unplaced {}
 
-        cubes[cube_id].points.iter().for_each(|p| {
-            beacons.insert(*p);
-        });
+impl From<Unplaced> for Error {
+    fn from(e: Unplaced) -> Self {
+        Self::NoSolution(e.to_string())
     }
+}
 
-    let max_manhattan_sum = scanners
-        .iter()
-        .map(|c1| {
-            scanners
-                .iter()
-                .map(|c2| c1.manhattan_distance(c2).sum())
-                .max()
-                .unwrap()
-        })
-        .max()
-        .unwrap();
-
-    return (beacons.len(), max_manhattan_sum as usize);
-
-    fn dfs(g: &UndirectedCsrGraph<usize>, end: usize) -> Vec<(usize, usize)> {
-        fn dfs_inner(
-            g: &UndirectedCsrGraph<usize>,
-            current: usize,
-            end: usize,
-            path: &mut Vec<(usize, usize)>,
-            visited: &mut HashSet<usize>,
-        ) -> bool {
-            if current == end {
-                return true;
+fn compute<const N: usize>(
+    cubes: &[Cube<N>],
+    threshold: usize,
+) -> Result<(usize, usize), Unplaced> {
+    let map = reconstruct(cubes, threshold)?;
+    Ok((map.beacons.len(), map.max_distance() as usize))
+}
+
+pub fn reconstruct<const N: usize>(
+    cubes: &[Cube<N>],
+    threshold: usize,
+) -> Result<Map<N>, Unplaced> {
+    reconstruct_threads(cubes, threshold, parallel::threads())
+}
+
+fn reconstruct_threads<const N: usize>(
+    cubes: &[Cube<N>],
+    threshold: usize,
+    threads: usize,
+) -> Result<Map<N>, Unplaced> {
+    let mut transforms = HashMap::new();
+    let mut neighbors = vec![vec![]; cubes.len()];
+
+    let candidates = Index::new(cubes)
+        .matches(threshold)
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let alignments = parallel::map(candidates.len(), threads, |i| {
+        let ((id_left, id_right), matches) = &candidates[i];
+        align(matches, threshold).map(|transform| (*id_left, *id_right, transform))
+    });
+
+    // transformations of overlapping cubes into each other's frame
+    for (id_left, id_right, transform) in alignments.into_iter().flatten() {
+        transforms.insert((id_left, id_right), transform);
+        transforms.insert((id_right, id_left), transform.inverse());
+        neighbors[id_left].push(id_right);
+        neighbors[id_right].push(id_left);
+    }
+
+    // Place cubes along a breadth first spanning tree rooted at cube 0.
+    // Each cube is placed relative to its parent in the tree, so every
+    // transformation is composed once and chains are as short as possible.
+    let mut placed: Vec<Option<Scanner<N>>> = vec![None; cubes.len()];
+    let mut queue = VecDeque::from([0]);
+    placed[0] = Some(Scanner {
+        id: cubes[0].id,
+        transform: Transform::IDENTITY,
+    });
+
+    while let Some(from) = queue.pop_front() {
+        let parent = placed[from].clone().unwrap();
+
+        for &to in &neighbors[from] {
+            if placed[to].is_some() {
+                continue;
             }
-            for neighbor in g.neighbors(current) {
-                if visited.contains(neighbor) {
-                    continue;
-                }
-                visited.insert(*neighbor);
-                path.push((current, *neighbor));
-                if !dfs_inner(g, *neighbor, end, path, visited) {
-                    path.pop();
-                } else {
-                    return true;
+
+            placed[to] = Some(Scanner {
+                id: cubes[to].id,
+                transform: parent.transform.compose(&transforms[&(from, to)]),
+            });
+            queue.push_back(to);
+        }
+    }
+
+    // the remaining cubes, grouped by the overlaps among them
+    let mut groups = vec![];
+    let mut grouped = placed.iter().map(Option::is_some).collect::<Vec<_>>();
+
+    for start in 0..cubes.len() {
+        if grouped[start] {
+            continue;
+        }
+
+        let mut group = vec![];
+        let mut queue = VecDeque::from([start]);
+        grouped[start] = true;
+
+        while let Some(from) = queue.pop_front() {
+            group.push(cubes[from].id);
+            for &to in &neighbors[from] {
+                if !grouped[to] {
+                    grouped[to] = true;
+                    queue.push_back(to);
                 }
             }
-            false
         }
 
-        let mut path = vec![];
-        let mut visited = HashSet::new();
-        visited.insert(0);
-
-        dfs_inner(g, 0, end, &mut path, &mut visited);
+        group.sort_unstable();
+        groups.push(group);
+    }
 
-        path
+    if !groups.is_empty() {
+        return Err(Unplaced { groups, threshold });
     }
-}
 
-#[derive(PartialEq, PartialOrd, Clone, Copy, Default, Eq, Hash, Ord)]
-struct Vector {
-    x: i32,
-    y: i32,
-    z: i32,
+    let scanners = placed.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+
+    let mut beacons = cubes
+        .iter()
+        .zip(&scanners)
+        .flat_map(|(cube, scanner)| cube.points.iter().map(|p| scanner.transform.apply(*p)))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    beacons.sort_unstable();
+
+    Ok(Map { scanners, beacons })
 }
 
-impl Sub for Vector {
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy, Eq, Hash, Ord)]
+pub struct Vector<const N: usize = 3>(pub [i32; N]);
+
+impl<const N: usize> Sub for Vector<N> {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
-            z: self.z - rhs.z,
-        }
+        Self(std::array::from_fn(|i| self.0[i] - rhs.0[i]))
     }
 }
 
-impl Add for Vector {
+impl<const N: usize> Add for Vector<N> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-            z: self.z + rhs.z,
-        }
+        Self(std::array::from_fn(|i| self.0[i] + rhs.0[i]))
     }
 }
-impl std::fmt::Display for Vector {
+
+impl<const N: usize> std::fmt::Display for Vector<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{},{},{}", self.x, self.y, self.z)
+        let coordinates = self.0.map(|c| c.to_string());
+        write!(f, "{}", coordinates.join(","))
     }
 }
 
-impl FromStr for Vector {
-    type Err = ();
+impl<const N: usize> FromStr for Vector<N> {
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, s) = s.split_once(',').unwrap();
-        let (y, z) = s.split_once(',').unwrap();
+        let mut coordinates = [0; N];
+        let mut rest = s;
+
+        for (i, c) in coordinates.iter_mut().enumerate() {
+            let token = if i + 1 < N {
+                let (token, tail) = split_once(s, rest, ",")?;
+                rest = tail;
+                token
+            } else {
+                rest
+            };
+            *c = parse_number(s, token)?;
+        }
+
+        Ok(Self(coordinates))
+    }
+}
+
+// The name of the `i`th axis: x, y, z, followed by x3, x4, ...
+fn axis_name(i: usize) -> String {
+    match i {
+        0 => "x".to_string(),
+        1 => "y".to_string(),
+        2 => "z".to_string(),
+        i => format!("x{}", i),
+    }
+}
 
-        Ok(Self {
-            x: x.parse::<i32>().unwrap(),
-            y: y.parse::<i32>().unwrap(),
-            z: z.parse::<i32>().unwrap(),
+// The orientations of a scanner, which are the permutation matrices with
+// entries of ±1: axis `i` becomes axis `permutation[i]`, multiplied by the
+// sign of the column. From three dimensions on these are the rotations,
+// the matrices with a determinant of 1, e.g. 24 in 3D. Flat maps can also
+// be turned over, so in 2D all 8 rotations and reflections are included.
+fn rotations<const N: usize>() -> Vec<Matrix<N>> {
+    let mut permutations = vec![];
+    permute(&mut std::array::from_fn(|i| i), 0, &mut permutations);
+
+    permutations
+        .into_iter()
+        .flat_map(|permutation: [usize; N]| {
+            // the sign of the permutation, by its number of inversions
+            let inversions = (0..N)
+                .flat_map(|i| (i + 1..N).map(move |j| (i, j)))
+                .filter(|&(i, j)| permutation[i] > permutation[j])
+                .count();
+            let parity = if inversions % 2 == 0 { 1 } else { -1 };
+
+            (0..1 << N)
+                .map(|bits: usize| {
+                    std::array::from_fn::<i32, N, _>(|i| if bits & (1 << i) == 0 { 1 } else { -1 })
+                })
+                .filter(move |sign| N < 3 || parity * sign.iter().product::<i32>() == 1)
+                .map(move |sign| {
+                    let mut rotation = [[0; N]; N];
+                    (0..N).for_each(|i| rotation[permutation[i]][i] = sign[i]);
+                    rotation
+                })
         })
+        .collect()
+}
+
+// All permutations of `items[k..]`, in lexicographic order.
+fn permute<const N: usize>(items: &mut [usize; N], k: usize, out: &mut Vec<[usize; N]>) {
+    if k == N {
+        out.push(*items);
+        return;
+    }
+
+    for i in k..N {
+        items[k..=i].rotate_right(1);
+        permute(items, k + 1, out);
+        items[k..=i].rotate_left(1);
     }
 }
 
-impl Vector {
-    fn mapping(&self, other: &Self) -> ([usize; 3], [i8; 3]) {
-        let mut rotation = [0; 3];
-        let mut sign = [1; 3];
-
-        let (r0, s0) = if self.x.abs() == other.x.abs() {
-            (0, if self.x == other.x { 1 } else { -1 })
-        } else if self.x.abs() == other.y.abs() {
-            (1, if self.x == other.y { 1 } else { -1 })
-        } else {
-            (2, if self.x == other.z { 1 } else { -1 })
-        };
-        rotation[0] = r0;
-        sign[0] = s0;
-
-        let (r1, s1) = if self.y.abs() == other.x.abs() {
-            (0, if self.y == other.x { 1 } else { -1 })
-        } else if self.y.abs() == other.y.abs() {
-            (1, if self.y == other.y { 1 } else { -1 })
-        } else {
-            (2, if self.y == other.z { 1 } else { -1 })
-        };
+// An NxN matrix, in rows.
+type Matrix<const N: usize> = [[i32; N]; N];
 
-        rotation[1] = r1;
-        sign[1] = s1;
+// A rotation followed by a translation, mapping points
+// of one scanner into the coordinates of another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transform<const N: usize = 3> {
+    pub rotation: Matrix<N>,
+    pub translation: Vector<N>,
+}
 
-        let (r2, s2) = if self.z.abs() == other.x.abs() {
-            (0, if self.z == other.x { 1 } else { -1 })
-        } else if self.z.abs() == other.y.abs() {
-            (1, if self.z == other.y { 1 } else { -1 })
-        } else {
-            (2, if self.z == other.z { 1 } else { -1 })
-        };
+impl<const N: usize> Transform<N> {
+    const IDENTITY: Self = {
+        let mut rotation = [[0; N]; N];
+        let mut i = 0;
+        while i < N {
+            rotation[i][i] = 1;
+            i += 1;
+        }
 
-        rotation[2] = r2;
-        sign[2] = s2;
+        Self {
+            rotation,
+            translation: Vector([0; N]),
+        }
+    };
 
-        (rotation, sign)
+    fn rotation(rotation: Matrix<N>) -> Self {
+        Self {
+            rotation,
+            translation: Vector([0; N]),
+        }
     }
 
-    fn rotate(&self, rotation: [usize; 3], sign: [i8; 3]) -> Self {
-        let mut res = Self { x: 0, y: 0, z: 0 };
+    pub fn apply(&self, p: Vector<N>) -> Vector<N> {
+        self.turn(p) + self.translation
+    }
 
-        let target = match rotation[0] {
-            0 => &mut res.x,
-            1 => &mut res.y,
-            2 => &mut res.z,
-            _ => unreachable!(),
-        };
+    // Applies only the rotation, e.g. to directions.
+    pub fn turn(&self, v: Vector<N>) -> Vector<N> {
+        Vector(
+            self.rotation
+                .map(|row| row.iter().zip(v.0).map(|(a, b)| a * b).sum()),
+        )
+    }
 
-        *target = self.x * sign[0] as i32;
+    // The transformation applying `other` first and `self` second.
+    pub fn compose(&self, other: &Self) -> Self {
+        let mut rotation = [[0; N]; N];
+        for (i, row) in rotation.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..N)
+                    .map(|k| self.rotation[i][k] * other.rotation[k][j])
+                    .sum();
+            }
+        }
 
-        let target = match rotation[1] {
-            0 => &mut res.x,
-            1 => &mut res.y,
-            2 => &mut res.z,
-            _ => unreachable!(),
-        };
+        Self {
+            rotation,
+            translation: self.apply(other.translation),
+        }
+    }
 
-        *target = self.y * sign[1] as i32;
+    // Rotations and reflections are orthogonal,
+    // so the inverse is the transposed matrix.
+    pub fn inverse(&self) -> Self {
+        let mut rotation = [[0; N]; N];
+        for (i, row) in rotation.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = self.rotation[j][i];
+            }
+        }
 
-        let target = match rotation[2] {
-            0 => &mut res.x,
-            1 => &mut res.y,
-            2 => &mut res.z,
-            _ => unreachable!(),
-        };
+        let inverse = Self::rotation(rotation);
+        Self {
+            rotation,
+            translation: Vector([0; N]) - inverse.turn(self.translation),
+        }
+    }
+}
 
-        *target = self.z * sign[2] as i32;
+impl<const N: usize> Vector<N> {
+    // The unit vectors along each axis.
+    fn axes() -> [Self; N] {
+        std::array::from_fn(|i| Self(std::array::from_fn(|j| (i == j) as i32)))
+    }
 
-        res
+    // The signed name of the axis a unit vector points along.
+    fn direction(&self) -> String {
+        match self.0.iter().position(|c| *c != 0) {
+            Some(i) if self.0[i].abs() == 1 && self.0.iter().filter(|c| **c != 0).count() == 1 => {
+                let sign = if self.0[i] > 0 { '+' } else { '-' };
+                format!("{}{}", sign, axis_name(i))
+            }
+            _ => panic!("{} is not a unit vector along an axis", self),
+        }
     }
 
-    fn center(left: &Self, right: &Self) -> Self {
-        let c_x = if right.x > 0 {
-            left.x - right.x.abs()
-        } else {
-            left.x + right.x.abs()
-        };
+    // The coordinates for 3D point cloud formats,
+    // where flat maps lie in the z = 0 plane.
+    fn xyz(&self) -> io::Result<[i32; 3]> {
+        if N > 3 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("cannot export {} dimensions as x, y and z", N),
+            ));
+        }
 
-        let c_y = if right.y > 0 {
-            left.y - right.y.abs()
-        } else {
-            left.y + right.y.abs()
-        };
+        Ok(std::array::from_fn(|i| self.0.get(i).copied().unwrap_or(0)))
+    }
 
-        let c_z = if right.z > 0 {
-            left.z - right.z.abs()
-        } else {
-            left.z + right.z.abs()
-        };
+    fn manhattan_distance(&self, other: &Self) -> u32 {
+        self.0.iter().zip(other.0).map(|(a, b)| a.abs_diff(b)).sum()
+    }
+}
 
-        Vector {
-            x: c_x,
-            y: c_y,
-            z: c_z,
+// The transformation mapping the right points of the pairs onto the left
+// points. For each orientation, all pairs vote for the translation that
+// would make them coincide.
+fn align<const N: usize>(
+    pairs: &[(Vector<N>, Vector<N>)],
+    threshold: usize,
+) -> Option<Transform<N>> {
+    rotations().into_iter().find_map(|rotation| {
+        let rotation = Transform::rotation(rotation);
+        let mut votes = HashMap::new();
+
+        for (p_self, p_other) in pairs {
+            let translation = *p_self - rotation.turn(*p_other);
+            let count = votes.entry(translation).or_insert(0);
+            *count += 1;
+            if *count >= threshold {
+                return Some(Transform {
+                    translation,
+                    ..rotation
+                });
+            }
         }
-    }
 
-    fn manhattan_distance(&self, other: &Self) -> Self {
-        Self {
-            x: self.x.abs_diff(other.x) as i32,
-            y: self.y.abs_diff(other.y) as i32,
-            z: self.z.abs_diff(other.z) as i32,
+        None
+    })
+}
+
+// The fingerprint of a pair of beacons, which does not change when
+// the scanner turns: the sorted absolute differences of coordinates.
+fn fingerprint<const N: usize>(a: &Vector<N>, b: &Vector<N>) -> [u32; N] {
+    let mut d = std::array::from_fn(|i| a.0[i].abs_diff(b.0[i]));
+    d.sort_unstable();
+    d
+}
+
+// The pairs of beacons of all cubes by fingerprint, as cube and point
+// indices. A beacon seen by two cubes has the same fingerprints with all
+// other beacons seen by both, so overlapping cubes and matching beacons
+// are found by lookup instead of comparing the beacons of all cube pairs.
+struct Index<'a, const N: usize> {
+    cubes: &'a [Cube<N>],
+    pairs: HashMap<[u32; N], Vec<(usize, usize, usize)>>,
+}
+
+impl<'a, const N: usize> Index<'a, N> {
+    fn new(cubes: &'a [Cube<N>]) -> Self {
+        let mut pairs = HashMap::<_, Vec<_>>::new();
+
+        for (c, cube) in cubes.iter().enumerate() {
+            for (i, a) in cube.points.iter().enumerate() {
+                for (j, b) in cube.points.iter().enumerate().skip(i + 1) {
+                    pairs.entry(fingerprint(a, b)).or_default().push((c, i, j));
+                }
+            }
         }
+
+        Self { cubes, pairs }
     }
 
-    fn sum(&self) -> i32 {
-        self.x + self.y + self.z
+    // Candidate pairs of the same beacon for all pairs of cubes sharing at
+    // least `threshold` of them, keyed by the cube indices in ascending
+    // order. Two beacons are candidates if they share `threshold - 1`
+    // fingerprints, one with each other beacon seen by both cubes.
+    fn matches(&self, threshold: usize) -> BTreeMap<(usize, usize), Vec<(Vector<N>, Vector<N>)>> {
+        let mut votes = HashMap::<_, HashMap<_, usize>>::new();
+
+        // cube indices in each list are ascending, as cubes are added in order
+        for pairs in self.pairs.values() {
+            for (k, &(c1, i1, j1)) in pairs.iter().enumerate() {
+                for &(c2, i2, j2) in pairs[k + 1..].iter().filter(|(c2, ..)| *c2 != c1) {
+                    let votes = votes.entry((c1, c2)).or_default();
+                    for p in [i1, j1] {
+                        for q in [i2, j2] {
+                            *votes.entry((p, q)).or_default() += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        votes
+            .into_iter()
+            .filter_map(|((c1, c2), votes)| {
+                let mut matches = votes
+                    .into_iter()
+                    .filter(|(_, count)| count + 1 >= threshold)
+                    .map(|((p, q), _)| (p, q))
+                    .collect::<Vec<_>>();
+
+                if matches.len() < threshold {
+                    return None;
+                }
+
+                matches.sort_unstable();
+                let points = matches
+                    .into_iter()
+                    .map(|(p, q)| (self.cubes[c1].points[p], self.cubes[c2].points[q]))
+                    .collect();
+
+                Some(((c1, c2), points))
+            })
+            .collect()
     }
 }
 
-struct Cube {
+#[derive(Clone)]
+pub struct Cube<const N: usize = 3> {
     id: u32,
-    points: Vec<Vector>,
+    points: Vec<Vector<N>>,
 }
 
-impl std::fmt::Display for Cube {
+impl<const N: usize> std::fmt::Display for Cube<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Scanner: {}", self.id)?;
         self.points.iter().try_for_each(|p| writeln!(f, "{}", p))?;
@@ -306,128 +675,434 @@ impl std::fmt::Display for Cube {
     }
 }
 
-impl FromStr for Cube {
-    type Err = ();
+impl<const N: usize> FromStr for Cube<N> {
+    type Err = ParseError;
 
+    // Line numbers of errors are relative to the scanner header.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let lines = s.lines().map(|line| line.trim()).collect::<Vec<_>>();
-        let id = lines[0].split(' ').nth(2).unwrap().parse::<u32>().unwrap();
-        let points = lines
-            .iter()
-            .skip(1)
-            .map(|line| line.parse::<Vector>().unwrap())
-            .collect::<Vec<_>>();
+        let lines = s.lines().collect::<Vec<_>>();
+        let header = lines.first().copied().unwrap_or(s);
+        let trimmed = header.trim();
 
-        Ok(Self { id, points })
-    }
-}
-
-impl Cube {
-    fn distances(&self) -> Vec<HashSet<[u32; 3]>> {
-        self.points
-            .iter()
-            .map(|p| {
-                self.points
-                    .iter()
-                    .filter(|other| *p != **other)
-                    .map(|other| {
-                        let mut d = [0; 3];
-
-                        d[0] = p.x.abs_diff(other.x);
-                        d[1] = p.y.abs_diff(other.y);
-                        d[2] = p.z.abs_diff(other.z);
-
-                        d.sort_unstable();
-                        d
-                    })
-                    .collect::<HashSet<_>>()
-            })
-            .collect::<Vec<_>>()
-    }
+        let id = trimmed
+            .strip_prefix("--- scanner ")
+            .and_then(|rest| rest.strip_suffix(" ---"))
+            .ok_or_else(|| ParseError::at(header, trimmed, "'--- scanner <id> ---'").at_line(1))?;
+        let id = parse_number(header, id).map_err(|e| e.at_line(1))?;
 
-    fn overlap(&self, other: &Self) -> Vec<(Vector, Vector)> {
-        let d_self = self.distances();
-        let d_other = other.distances();
-
-        let matches = d_self
+        let points = lines
             .iter()
             .enumerate()
-            .flat_map(|(i_self, p_self)| {
-                d_other
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(i_other, p_other)| {
-                        if p_self.intersection(p_other).count() == 11 {
-                            Some((self.points[i_self], other.points[i_other]))
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>()
+            .skip(1)
+            .map(|(i, line)| {
+                line.trim()
+                    .parse::<Vector<N>>()
+                    .map_err(|e| e.within(line, line.trim()).at_line(i + 1))
             })
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>, _>>()?;
 
-        matches
+        Ok(Self { id, points })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use test::Bencher;
+
     use super::*;
+    use crate::{answers::expected, solution::Part};
 
     #[test]
     fn test_compute_sample() {
         let f = std::fs::read_to_string("input/d19-test.txt").expect("file not found");
 
-        let (beacons, distance) = run(&f);
+        let (beacons, distance) = compute(&parse(&f).unwrap(), THRESHOLD).unwrap();
 
         assert_eq!(beacons, 79);
         assert_eq!(distance, 3621);
+
+        for threads in [2, 4] {
+            assert_eq!(
+                reconstruct_threads(&parse(&f).unwrap(), THRESHOLD, threads),
+                reconstruct(&parse(&f).unwrap(), THRESHOLD)
+            );
+        }
     }
 
     #[test]
-    fn test_compute() {
-        let f = std::fs::read_to_string("input/d19.txt").expect("file not found");
+    fn test_reconstruct_sample() {
+        let f = std::fs::read_to_string("input/d19-test.txt").expect("file not found");
 
-        let (beacons, distance) = run(&f);
+        let map = reconstruct(&parse(&f).unwrap(), THRESHOLD).unwrap();
 
-        assert_eq!(beacons, 398);
-        assert_eq!(distance, 10965);
+        assert_eq!(
+            map.scanners
+                .iter()
+                .map(|s| (s.id, s.position().to_string()))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, "0,0,0".to_string()),
+                (1, "68,-1246,-43".to_string()),
+                (2, "1105,-1205,1229".to_string()),
+                (3, "-92,-2380,-20".to_string()),
+                (4, "-20,-1133,1061".to_string()),
+            ]
+        );
+        assert_eq!(map.scanners[0].transform, Transform::IDENTITY);
+        assert_eq!(
+            map.scanners[1].axes().map(|axis| axis.direction()),
+            ["-x", "+y", "-z"]
+        );
+
+        assert_eq!(map.beacons.len(), 79);
+        assert_eq!(map.beacons[0].to_string(), "-892,524,684");
+        assert_eq!(map.beacons[78].to_string(), "1994,-1805,1792");
+        assert_eq!(map.max_distance(), 3621);
     }
 
     #[test]
-    fn test_distances() {
-        let distances = "--- scanner 0 ---
-                         42,84,42
-                         50,90,60
-                         55,-10,-42"
-            .parse::<Cube>()
+    fn test_export() {
+        let map = Map {
+            scanners: vec![
+                Scanner {
+                    id: 0,
+                    transform: Transform::IDENTITY,
+                },
+                Scanner {
+                    id: 1,
+                    transform: Transform {
+                        rotation: [[0, -1, 0], [1, 0, 0], [0, 0, 1]],
+                        translation: Vector([5, -7, 1]),
+                    },
+                },
+            ],
+            beacons: vec![Vector([-1, 2, 3]), Vector([4, 0, -6])],
+        };
+
+        let mut csv = vec![];
+        map.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "kind,id,x,y,z,x_axis,y_axis,z_axis
+scanner,0,0,0,0,+x,+y,+z
+scanner,1,5,-7,1,+y,-x,+z
+beacon,,-1,2,3,,,
+beacon,,4,0,-6,,,
+"
+        );
+
+        let mut ply = vec![];
+        map.write_ply(&mut ply).unwrap();
+        assert_eq!(
+            String::from_utf8(ply).unwrap(),
+            "ply
+format ascii 1.0
+element vertex 4
+property int x
+property int y
+property int z
+property uchar red
+property uchar green
+property uchar blue
+end_header
+0 0 0 255 0 0
+5 -7 1 255 0 0
+-1 2 3 255 255 255
+4 0 -6 255 255 255
+"
+        );
+
+        let mut xyz = vec![];
+        map.write_xyz(&mut xyz).unwrap();
+        assert_eq!(String::from_utf8(xyz).unwrap(), "-1 2 3\n4 0 -6\n");
+    }
+
+    #[test]
+    fn test_export_4d() {
+        let map = Map::<4> {
+            scanners: vec![Scanner {
+                id: 0,
+                transform: Transform::IDENTITY,
+            }],
+            beacons: vec![Vector([1, 2, 3, 4])],
+        };
+
+        let mut csv = vec![];
+        map.write_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 3);
+
+        let mut ply = vec![];
+        let e = map.write_ply(&mut ply).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(e.to_string(), "cannot export 4 dimensions as x, y and z");
+        assert!(ply.is_empty());
+
+        let mut xyz = vec![];
+        let e = map.write_xyz(&mut xyz).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert!(xyz.is_empty());
+    }
+
+    #[test]
+    fn test_rotations() {
+        let rotations = rotations();
+        assert_eq!(rotations.len(), 24);
+        assert_eq!(rotations[0], Transform::IDENTITY.rotation);
+
+        let v = Vector([1, 2, 3]);
+        let rotated = rotations
+            .iter()
+            .map(|rotation| Transform::rotation(*rotation).turn(v))
+            .collect::<HashSet<_>>();
+        assert_eq!(rotated.len(), 24);
+
+        // no reflections, e.g. mirroring at the x axis
+        assert!(!rotated.contains(&Vector([-1, 2, 3])));
+    }
+
+    #[test]
+    fn test_transform() {
+        let translation = Vector([5, -7, 11]);
+        let p = Vector([1, 2, 3]);
+
+        for rotation in rotations() {
+            let t = Transform {
+                rotation,
+                translation,
+            };
+
+            assert_eq!(t.compose(&t.inverse()), Transform::IDENTITY);
+            assert_eq!(t.inverse().compose(&t), Transform::IDENTITY);
+            assert_eq!(t.inverse().apply(t.apply(p)), p);
+            assert_eq!(t.inverse().inverse(), t);
+
+            for other in rotations() {
+                let u = Transform {
+                    rotation: other,
+                    translation: p,
+                };
+                assert_eq!(t.compose(&u).apply(p), t.apply(u.apply(p)));
+                assert!(rotations().contains(&t.compose(&u).rotation));
+            }
+        }
+
+        assert_eq!(Transform::IDENTITY.apply(p), p);
+    }
+
+    #[test]
+    fn test_rotations_per_dimension() {
+        fn check<const N: usize>(count: usize) {
+            let rotations = rotations::<N>();
+            assert_eq!(rotations.len(), count);
+            assert_eq!(rotations[0], Transform::<N>::IDENTITY.rotation);
+            assert_eq!(rotations.iter().collect::<HashSet<_>>().len(), count);
+
+            for rotation in rotations {
+                let t = Transform::rotation(rotation);
+                assert_eq!(t.compose(&t.inverse()), Transform::IDENTITY);
+            }
+        }
+
+        check::<1>(2);
+        check::<2>(8);
+        check::<3>(24);
+        check::<4>(192);
+    }
+
+    #[test]
+    fn test_reconstruct_2d() {
+        let points = (0..20)
+            .map(|i| Vector([(i * 37) % 101 - 50, (i * i * 13) % 97 - 48]))
+            .collect::<Vec<_>>();
+
+        // scanner 1 sees the map turned over, along the diagonal
+        let transform = Transform {
+            rotation: [[0, 1], [1, 0]],
+            translation: Vector([40, -17]),
+        };
+        let seen = points[4..]
+            .iter()
+            .map(|p| transform.inverse().apply(*p))
+            .collect::<Vec<_>>();
+
+        let input = [&points[..16], &seen[..]]
+            .iter()
+            .enumerate()
+            .map(|(id, points)| {
+                let lines = points.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+                format!("--- scanner {} ---\n{}", id, lines.join("\n"))
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let cubes = parse_scanners::<2>(&input).unwrap();
+        let map = reconstruct(&cubes, THRESHOLD).unwrap();
+
+        assert_eq!(map.scanners[1].transform, transform);
+        assert_eq!(
+            map.scanners[1].axes().map(|axis| axis.direction()),
+            ["+y", "+x"]
+        );
+        assert_eq!(map.beacons.len(), 20);
+        assert_eq!(map.max_distance(), 57);
+
+        let mut csv = vec![];
+        map.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("kind,id,x,y,x_axis,y_axis"));
+        assert_eq!(lines.next(), Some("scanner,0,0,0,+x,+y"));
+        assert_eq!(lines.next(), Some("scanner,1,40,-17,+y,+x"));
+
+        let mut xyz = vec![];
+        map.write_xyz(&mut xyz).unwrap();
+        assert!(String::from_utf8(xyz)
             .unwrap()
-            .distances();
+            .lines()
+            .all(|l| l.ends_with(" 0")));
+
+        let e = parse_scanners::<2>("--- scanner 0 ---\n1,2,3")
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 3: expected a number, found '2,3'"
+        );
+    }
 
-        distances.iter().for_each(|d| println!("{:?}", d));
+    #[test]
+    fn test_align() {
+        let scanners = INPUT
+            .split("\n\n")
+            .map(|s| s.parse::<Cube>().unwrap())
+            .collect::<Vec<_>>();
+
+        let pairs = Index::new(&scanners)
+            .matches(THRESHOLD)
+            .remove(&(0, 1))
+            .unwrap();
+
+        let transform = align(&pairs, THRESHOLD).unwrap();
+        assert_eq!(transform.translation, Vector([68, -1246, -43]));
+        assert_eq!(
+            transform.apply(Vector([686, 422, 578])),
+            Vector([-618, -824, -621])
+        );
+
+        // the sample scanners share exactly 12 beacons
+        assert_eq!(align(&pairs, THRESHOLD + 1), None);
+    }
 
-        let mut expected = vec![];
+    #[test]
+    fn test_align_equal_components() {
+        // the differences between all points have equal x and y
+        // components, which used to break inferring the orientation
+        let points = [
+            (0, 0, 0),
+            (3, 3, 7),
+            (-5, -5, 2),
+            (11, 11, -4),
+            (8, 8, 19),
+            (-13, -13, -9),
+            (21, 21, 5),
+            (-2, -2, 30),
+            (17, 17, -23),
+            (-29, -29, 13),
+            (26, 26, 26),
+            (-7, -7, -31),
+        ]
+        .map(|(x, y, z)| Vector([x, y, z]));
+
+        let transform = Transform {
+            rotation: [[0, -1, 0], [0, 0, -1], [1, 0, 0]],
+            translation: Vector([100, -20, 7]),
+        };
 
-        let mut d0 = HashSet::new();
-        d0.insert([6, 8, 18]);
-        d0.insert([13, 84, 94]);
+        let cubes = vec![
+            Cube {
+                id: 0,
+                points: points.iter().map(|p| transform.apply(*p)).collect(),
+            },
+            Cube {
+                id: 1,
+                points: points.to_vec(),
+            },
+        ];
+
+        assert_eq!(compute(&cubes, THRESHOLD), Ok((12, 127)));
+    }
 
-        expected.push(d0);
+    #[test]
+    fn test_unplaced() {
+        let f = std::fs::read_to_string("input/d19-test.txt").expect("file not found");
+        // beacons that no other scanner detects
+        let noise = |id: u32| Cube {
+            id,
+            points: (0..12)
+                .map(|i| Vector([i, i * i * (id as i32 + 1), 5000]))
+                .collect(),
+        };
 
-        let mut d0 = HashSet::new();
-        d0.insert([5, 100, 102]);
-        d0.insert([6, 8, 18]);
+        let mut cubes = parse(&f).unwrap();
+        cubes.push(noise(5));
+
+        let e = compute(&cubes, THRESHOLD).err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "cannot place scanner 5, no chain of scanners sharing at least 12 beacons leads to scanner 0"
+        );
+
+        // the sample scanners are connected, but not to the new scanner 0
+        let mut cubes = parse(&f).unwrap();
+        cubes.iter_mut().for_each(|cube| cube.id += 1);
+        cubes.insert(0, noise(0));
+        cubes.push(noise(6));
+
+        let e = compute(&cubes, THRESHOLD).err().unwrap();
+        assert_eq!(e.groups, vec![vec![1, 2, 3, 4, 5], vec![6]]);
+        assert_eq!(
+            e.to_string(),
+            "cannot place 2 groups of scanners {1, 2, 3, 4, 5}, {6}, \
+             no chain of scanners sharing at least 12 beacons leads to scanner 0"
+        );
+
+        let e = compute(&parse(&f).unwrap(), 13).err().unwrap();
+        assert_eq!(e.groups, vec![vec![1], vec![2], vec![3], vec![4]]);
+    }
 
-        expected.push(d0);
+    #[test]
+    fn test_fingerprints() {
+        let cube = "--- scanner 0 ---
+                    42,84,42
+                    50,90,60
+                    55,-10,-42"
+            .parse::<Cube>()
+            .unwrap();
+        let cubes = [cube];
+        let index = Index::new(&cubes);
 
-        let mut d0 = HashSet::new();
-        d0.insert([5, 100, 102]);
-        d0.insert([13, 84, 94]);
+        let mut expected = HashMap::new();
+        expected.insert([6, 8, 18], vec![(0, 0, 1)]);
+        expected.insert([13, 84, 94], vec![(0, 0, 2)]);
+        expected.insert([5, 100, 102], vec![(0, 1, 2)]);
 
-        expected.push(d0);
+        assert_eq!(index.pairs, expected);
+    }
 
-        assert_eq!(distances, expected);
+    #[test]
+    fn test_parse_error() {
+        let e = parse("--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n1,2,3\n4,x,6")
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 6, column 3: expected a number, found 'x'"
+        );
+
+        let e = parse("--- scanner 0 ---\n1,2,3\n\n--- sensor 1 ---\n1,2,3")
+            .err()
+            .unwrap();
+        assert_eq!(e.line(), Some(4));
     }
 
     #[test]
@@ -437,8 +1112,14 @@ mod tests {
             .map(|s| s.parse::<Cube>().unwrap())
             .collect::<Vec<_>>();
 
-        let overlap = scanners[0].overlap(&scanners[1]);
-        assert_eq!(overlap.len(), 12);
+        let index = Index::new(&scanners);
+
+        let matches = index.matches(THRESHOLD);
+        assert_eq!(matches.keys().collect::<Vec<_>>(), vec![&(0, 1)]);
+        assert_eq!(matches[&(0, 1)].len(), 12);
+
+        // the sample scanners share exactly 12 beacons
+        assert!(index.matches(THRESHOLD + 1).is_empty());
     }
 
     const INPUT: &str = r#"--- scanner 0 ---
@@ -494,4 +1175,31 @@ mod tests {
                            807,-499,-711
                            755,-354,-619
                            553,889,-390"#;
+
+    #[bench]
+    fn bench_compute(b: &mut Bencher) {
+        let input =
+            parse(&std::fs::read_to_string("input/d19.txt").expect("file not found")).unwrap();
+
+        let expected = (expected(19, Part::One), expected(19, Part::Two));
+
+        b.iter(|| {
+            let map = reconstruct_threads(&input.clone(), THRESHOLD, 1).unwrap();
+            assert_eq!((map.beacons.len(), map.max_distance() as usize), expected)
+        });
+    }
+
+    #[bench]
+    fn bench_compute_parallel(b: &mut Bencher) {
+        let input =
+            parse(&std::fs::read_to_string("input/d19.txt").expect("file not found")).unwrap();
+
+        let expected = (expected(19, Part::One), expected(19, Part::Two));
+        let threads = std::thread::available_parallelism().unwrap().get();
+
+        b.iter(|| {
+            let map = reconstruct_threads(&input.clone(), THRESHOLD, threads).unwrap();
+            assert_eq!((map.beacons.len(), map.max_distance() as usize), expected)
+        });
+    }
 }
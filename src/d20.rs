@@ -1,9 +1,28 @@
 use std::collections::HashSet;
 
-pub fn run(lines: &str) -> (usize, usize) {
-    let (algo, map) = parse(lines);
+use crate::{
+    error::{Error, ParseError},
+    solution::Solution,
+};
 
-    (part1(map.clone(), &algo), part2(map, &algo))
+type Image = HashSet<(isize, isize)>;
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = (Vec<bool>, Image);
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1((algo, map): &Self::Input) -> usize {
+        part1(map.clone(), algo)
+    }
+
+    fn part2((algo, map): &Self::Input) -> usize {
+        part2(map.clone(), algo)
+    }
 }
 
 fn part1(map: HashSet<(isize, isize)>, algo: &[bool]) -> usize {
@@ -37,10 +56,11 @@ fn step(
     algo: &[bool],
     draw_infinite: bool,
 ) -> HashSet<(isize, isize)> {
-    let r_min = *map.iter().map(|(r, _)| r).min().unwrap();
-    let r_max = *map.iter().map(|(r, _)| r).max().unwrap();
-    let c_min = *map.iter().map(|(_, c)| c).min().unwrap();
-    let c_max = *map.iter().map(|(_, c)| c).max().unwrap();
+    // An image without lit pixels is treated as a single dark one.
+    let r_min = map.iter().map(|(r, _)| *r).min().unwrap_or(0);
+    let r_max = map.iter().map(|(r, _)| *r).max().unwrap_or(0);
+    let c_min = map.iter().map(|(_, c)| *c).min().unwrap_or(0);
+    let c_max = map.iter().map(|(_, c)| *c).max().unwrap_or(0);
 
     let mut res = HashSet::new();
 
@@ -76,32 +96,52 @@ fn step(
     res
 }
 
-fn parse(lines: &str) -> (Vec<bool>, HashSet<(isize, isize)>) {
-    let (algo, map) = lines.split_once("\n\n").unwrap();
+fn parse(input: &str) -> Result<(Vec<bool>, Image), ParseError> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let line = |i: usize| lines.get(i).copied().unwrap_or(&input[input.len()..]);
+
+    let algo = pixels(line(0)).map_err(|e| e.at_line(1))?;
+    if algo.len() != 512 {
+        let first = line(0);
+        return Err(ParseError::new(
+            first.chars().count() + 1,
+            "512 pixels",
+            algo.len().to_string(),
+        )
+        .at_line(1));
+    }
 
-    let algo = algo.chars().map(|c| c == '#').collect::<Vec<_>>();
+    if !line(1).is_empty() {
+        return Err(ParseError::at(line(1), line(1), "an empty line").at_line(2));
+    }
 
-    assert_eq!(algo.len(), 512);
+    let mut map = HashSet::new();
 
-    let map = map
-        .lines()
-        .enumerate()
-        .flat_map(|(row, line)| {
-            line.trim()
-                .chars()
-                .enumerate()
-                .filter_map(|(col, bit)| {
-                    if bit == '#' {
-                        Some((row as isize, col as isize))
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<HashSet<_>>();
+    for (row, line) in lines.iter().enumerate().skip(2) {
+        let pixels =
+            pixels(line.trim()).map_err(|e| e.within(line, line.trim()).at_line(row + 1))?;
 
-    (algo, map)
+        for (col, _) in pixels.iter().enumerate().filter(|(_, lit)| **lit) {
+            map.insert(((row - 2) as isize, col as isize));
+        }
+    }
+
+    Ok((algo, map))
+}
+
+// A line of '#' and '.' as lit and dark pixels.
+fn pixels(line: &str) -> Result<Vec<bool>, ParseError> {
+    line.char_indices()
+        .map(|(i, c)| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(ParseError::at(
+                line,
+                &line[i..i + c.len_utf8()],
+                "'#' or '.'",
+            )),
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -113,7 +153,7 @@ mod tests {
     fn test_parse() {
         let lines = std::fs::read_to_string("input/d20-test.txt").expect("file not found");
 
-        let (algo, map) = parse(&lines);
+        let (algo, map) = parse(&lines).unwrap();
 
         assert!(map.contains(&(0, 0)));
         assert!(map.contains(&(1, 0)));
@@ -134,35 +174,44 @@ mod tests {
     fn test_part1_sample() {
         let lines = std::fs::read_to_string("input/d20-test.txt").expect("file not found");
 
-        let (algo, map) = parse(&lines);
+        let (algo, map) = parse(&lines).unwrap();
 
         assert_eq!(part1(map, &algo), 35);
     }
 
-    #[test]
-    fn test_part1_input() {
-        let lines = std::fs::read_to_string("input/d20.txt").expect("file not found");
-
-        let (algo, map) = parse(&lines);
-
-        assert_eq!(part1(map, &algo), 5359);
-    }
-
     #[test]
     fn test_part2_sample() {
         let lines = std::fs::read_to_string("input/d20-test.txt").expect("file not found");
 
-        let (algo, map) = parse(&lines);
+        let (algo, map) = parse(&lines).unwrap();
 
         assert_eq!(part2(map, &algo), 3351);
     }
 
     #[test]
-    fn test_part2_input() {
-        let lines = std::fs::read_to_string("input/d20.txt").expect("file not found");
-
-        let (algo, map) = parse(&lines);
+    fn test_parse_error() {
+        let lines = std::fs::read_to_string("input/d20-test.txt").expect("file not found");
 
-        assert_eq!(part2(map, &algo), 12333);
+        let column = lines.find("#..#.").unwrap() + 4;
+        let e = parse(&lines.replacen("#..#.", "#..x.", 1)).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            format!("line 1, column {}: expected '#' or '.', found 'x'", column)
+        );
+
+        let e = parse(&lines.replace("\n#..#.\n", "\n#..#o\n")).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 5: expected '#' or '.', found 'o'"
+        );
+
+        let e = parse("..#\n\n#.").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 4: expected 512 pixels, found 3"
+        );
+
+        let (algo, map) = parse(&lines.replace('#', ".")).unwrap();
+        assert_eq!(part1(map, &algo), 0);
     }
 }
@@ -1,7 +1,26 @@
-use std::{collections::HashMap, num::ParseIntError, str::FromStr};
+use std::{collections::HashMap, str::FromStr};
 
-pub fn run(players: Vec<Player>) -> (usize, usize) {
-    (part1(players.clone()), part2(players))
+use crate::{
+    error::{parse_number, split_once, Error, ParseError},
+    solution::{parse_lines, Solution},
+};
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input = Vec<Player>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input)
+    }
+
+    fn part1(players: &Self::Input) -> usize {
+        part1(players.clone())
+    }
+
+    fn part2(players: &Self::Input) -> usize {
+        part2(players.clone())
+    }
 }
 
 #[derive(Debug, PartialEq, PartialOrd, Clone, Copy, Hash, Eq)]
@@ -11,11 +30,15 @@ pub struct Player {
 }
 
 impl FromStr for Player {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts = s.trim().split(' ').collect::<Vec<_>>();
-        let pos = parts[4].parse::<u8>()?;
+        let line = s.trim();
+        let (_, pos) = split_once(s, line, " starting position: ")?;
+        let pos = match parse_number::<u8>(s, pos)? {
+            pos @ 1..=10 => pos,
+            _ => return Err(ParseError::at(s, pos, "a position from 1 to 10")),
+        };
         let score = 0;
 
         Ok(Self { pos, score })
@@ -152,6 +175,21 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_parse_error() {
+        let e = "Player 1 starting position: 11"
+            .parse::<Player>()
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 29: expected a position from 1 to 10, found '11'"
+        );
+
+        let e = "Player 1 position: 4".parse::<Player>().err().unwrap();
+        assert_eq!(e.column(), 1);
+    }
+
     #[test]
     fn test_dice() {
         let mut dice = Dice::new();
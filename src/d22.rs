@@ -1,7 +1,26 @@
-use std::{collections::HashSet, fmt::Display, num::ParseIntError, ops::Sub, str::FromStr};
+use std::{collections::HashSet, fmt::Display, ops::Sub, str::FromStr};
 
-pub fn run(cuboids: Vec<Cuboid>) -> (usize, usize) {
-    (part1(&cuboids), part2(&cuboids))
+use crate::{
+    error::{parse_number, split_once, Error, ParseError},
+    solution::{parse_lines, Solution},
+};
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input = Vec<Cuboid>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input)
+    }
+
+    fn part1(cuboids: &Self::Input) -> usize {
+        part1(cuboids)
+    }
+
+    fn part2(cuboids: &Self::Input) -> usize {
+        part2(cuboids)
+    }
 }
 
 fn part1(cuboids: &[Cuboid]) -> usize {
@@ -69,14 +88,14 @@ impl From<(i32, i32)> for Range {
 }
 
 impl FromStr for Range {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.split_once('=').unwrap().1;
-        let (from, to) = s.split_once("..").unwrap();
+        let (_, range) = split_once(s, s, "=")?;
+        let (from, to) = split_once(s, range, "..")?;
         Ok(Self {
-            from: from.parse::<i32>()?,
-            to: to.parse::<i32>()?,
+            from: parse_number(s, from)?,
+            to: parse_number(s, to)?,
         })
     }
 }
@@ -121,21 +140,30 @@ pub struct Cuboid {
 }
 
 impl FromStr for Cuboid {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (state, ranges) = s.trim().split_once(' ').unwrap();
+        let (state, ranges) = split_once(s, s.trim(), " ")?;
+        let state = match state {
+            "on" => true,
+            "off" => false,
+            _ => return Err(ParseError::at(s, state, "'on' or 'off'")),
+        };
+
         let mut ranges = ranges.split(',');
-        let x = ranges.next().unwrap().parse::<Range>()?;
-        let y = ranges.next().unwrap().parse::<Range>()?;
-        let z = ranges.next().unwrap().parse::<Range>()?;
+        let mut range = |axis: &str| {
+            let range = ranges.next().unwrap_or(&s[s.len()..]);
+            if !range.starts_with(axis) {
+                return Err(ParseError::at(s, range, format!("'{}..'", axis)));
+            }
+            range.parse::<Range>().map_err(|e| e.within(s, range))
+        };
 
-        Ok(Self {
-            state: state == "on",
-            x,
-            y,
-            z,
-        })
+        let x = range("x=")?;
+        let y = range("y=")?;
+        let z = range("z=")?;
+
+        Ok(Self { state, x, y, z })
     }
 }
 
@@ -241,6 +269,7 @@ mod tests {
     use test::Bencher;
 
     use super::*;
+    use crate::{answers::expected, solution::Part};
 
     const SMALL_INPUT: &str = r#"on x=10..12,y=10..12,z=10..12
                                  on x=11..13,y=11..13,z=11..13
@@ -282,6 +311,30 @@ mod tests {
         parse(SMALL_INPUT).iter().for_each(|c| println!("{}", c));
     }
 
+    #[test]
+    fn test_parse_error() {
+        let e = "of x=10..12,y=10..12,z=10..12"
+            .parse::<Cuboid>()
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 1: expected 'on' or 'off', found 'of'"
+        );
+
+        let e = "on x=10..12,y=10..12".parse::<Cuboid>().err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 21: expected 'z=..', found end of line"
+        );
+
+        let e = "on x=10..12,y=10-12,z=10..12"
+            .parse::<Cuboid>()
+            .err()
+            .unwrap();
+        assert_eq!(e.to_string(), "column 15: expected '..', found '10-12'");
+    }
+
     #[test]
     fn test_part1() {
         assert_eq!(part1(&parse(SMALL_INPUT)), 39);
@@ -365,7 +418,9 @@ mod tests {
                 .as_str(),
         );
 
-        bencher.iter(|| assert_eq!(part1(&cuboids), 568000));
+        let expected = expected(22, Part::One);
+
+        bencher.iter(|| assert_eq!(part1(&cuboids), expected));
     }
 
     #[bench]
@@ -376,6 +431,8 @@ mod tests {
                 .as_str(),
         );
 
-        bencher.iter(|| assert_eq!(part2(&cuboids), 1177411289280259));
+        let expected = expected(22, Part::Two);
+
+        bencher.iter(|| assert_eq!(part2(&cuboids), expected));
     }
 }
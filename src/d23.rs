@@ -1,52 +1,267 @@
-use std::{collections::HashMap, fmt::Display};
-
-pub fn run() -> (usize, usize) {
-    let input1 = State::new(
-        ['.'; 11],
-        [
-            ['A', 'B', 'A', 'A'],
-            ['D', 'C', 'B', 'B'],
-            ['A', 'D', 'C', 'C'],
-            ['B', 'C', 'D', 'D'],
-        ],
-    );
-
-    let input2 = State::new(
-        ['.'; 11],
-        [
-            ['A', 'D', 'D', 'B'],
-            ['D', 'C', 'B', 'C'],
-            ['A', 'B', 'A', 'D'],
-            ['B', 'A', 'C', 'C'],
-        ],
-    );
-    (search(input1), search(input2))
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt::Display,
+    rc::Rc,
+    str::FromStr,
+};
+
+use crate::{
+    error::{Error, ParseError},
+    solution::{Part, Solution},
+};
+
+pub struct Day23;
+
+// The cheapest way to organize the folded and unfolded burrow.
+pub struct Solutions {
+    folded: Organized,
+    unfolded: Organized,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-struct State {
-    corridor: [char; 11],
-    rooms: [[char; 4]; 4],
-    locks: [[bool; 4]; 4],
+// The least energy and the moves organizing a burrow,
+// kept to replay them from the initial state.
+struct Organized {
+    start: State,
+    energy: usize,
+    moves: Vec<(Move, usize)>,
+}
+
+impl Solution for Day23 {
+    type Input = Solutions;
+
+    const SOLVES_IN_PARSE: bool = true;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let burrow = input.parse::<Burrow>()?;
+        let organize = |start: State| {
+            let (energy, moves) = solve(start.clone()).ok_or_else(unorganized)?;
+            Ok::<_, Error>(Organized {
+                start,
+                energy,
+                moves,
+            })
+        };
+
+        Ok(Solutions {
+            folded: organize(burrow.folded())?,
+            unfolded: organize(burrow.unfolded()?)?,
+        })
+    }
+
+    fn part1(solutions: &Self::Input) -> usize {
+        solutions.folded.energy
+    }
+
+    fn part2(solutions: &Self::Input) -> usize {
+        solutions.unfolded.energy
+    }
+}
+
+// The burrow as drawn in the puzzle input:
+//
+// #############
+// #...........#
+// ###B#C#B#D###
+//   #A#D#C#A#
+//   #########
+//
+// The hallway length, the number of rooms and their depth
+// are taken from the diagram. Room `i` is the home of the
+// `i`-th amphipod type, starting with 'A'.
+#[derive(Debug, PartialEq)]
+pub struct Burrow {
+    corridor: Vec<char>,
+    doors: Vec<usize>,
+    rows: Vec<Vec<char>>,
+}
+
+impl Burrow {
+    // Lines inserted between the two room rows for part 2.
+    const UNFOLDED: [[char; 4]; 2] = [['D', 'C', 'B', 'A'], ['D', 'B', 'A', 'C']];
+
+    // The state for part 1, the burrow as drawn.
+    fn folded(&self) -> State {
+        self.state(&self.rows)
+    }
+
+    // The state for part 2 with the folded part of the diagram
+    // inserted. This is only defined for the puzzle's four rooms.
+    fn unfolded(&self) -> Result<State, Error> {
+        if self.doors.len() != Self::UNFOLDED[0].len() {
+            return Err(Error::NoSolution(format!(
+                "only burrows with {} rooms can be unfolded, found {}",
+                Self::UNFOLDED[0].len(),
+                self.doors.len()
+            )));
+        }
+
+        let mut rows = self.rows.clone();
+        rows.splice(1..1, Self::UNFOLDED.iter().map(|row| row.to_vec()));
+
+        Ok(self.state(&rows))
+    }
+
+    fn state(&self, rows: &[Vec<char>]) -> State {
+        let rooms = (0..self.doors.len())
+            .map(|room| rows.iter().map(|row| row[room]).collect())
+            .collect();
+
+        State::with_doors(self.corridor.clone(), rooms, self.doors.clone())
+    }
+}
+
+impl FromStr for Burrow {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn cell(line: &str, col: usize, allowed: &str, expected: &str) -> Result<char, ParseError> {
+            match line.get(col..col + 1) {
+                Some(c) if allowed.contains(c) => Ok(c.chars().next().unwrap()),
+                Some(c) => Err(ParseError::at(line, c, expected)),
+                None => Err(ParseError::at(line, &line[line.len()..], expected)),
+            }
+        }
+
+        let lines = s.lines().collect::<Vec<_>>();
+        let line = |i: usize| lines.get(i).copied().unwrap_or(&s[s.len()..]);
+
+        // The hallway spans everything up to the next wall.
+        let hallway = line(1);
+        cell(hallway, 0, "#", "'#'").map_err(|e| e.at_line(2))?;
+        let len = hallway[1..]
+            .find('#')
+            .ok_or_else(|| ParseError::at(hallway, &hallway[hallway.len()..], "'#'").at_line(2))?;
+
+        // Every gap in the first room line is the door of a room.
+        let first = line(2);
+        let doors = first
+            .char_indices()
+            .filter(|(_, c)| *c != '#' && !c.is_whitespace())
+            .map(|(col, c)| match col {
+                col if (1..=len).contains(&col) => Ok(col - 1),
+                col => Err(ParseError::at(
+                    first,
+                    &first[col..col + c.len_utf8()],
+                    "'#'",
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.at_line(3))?;
+
+        if doors.is_empty() {
+            return Err(ParseError::at(first, first, "a room").at_line(3));
+        }
+
+        let species = (0..doors.len()).map(State::species).collect::<String>();
+        let allowed = format!(".{}", species);
+
+        let corridor = (1..=len)
+            .map(|col| cell(hallway, col, &allowed, "'.' or an amphipod"))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.at_line(2))?;
+
+        let mut rows = vec![];
+        while !line(rows.len() + 2).trim().trim_matches('#').is_empty() {
+            let i = rows.len() + 2;
+            let row = doors
+                .iter()
+                .enumerate()
+                .map(|(room, door)| {
+                    let col = door + 1;
+                    cell(line(i), col - 1, "#", "'#'")?;
+                    // Rooms fill up from the bottom, there is
+                    // no empty slot below an amphipod.
+                    let amphipod = if rows.iter().any(|row: &Vec<char>| row[room] != '.') {
+                        cell(line(i), col, &species, "an amphipod")?
+                    } else {
+                        cell(line(i), col, &allowed, "'.' or an amphipod")?
+                    };
+                    cell(line(i), col + 1, "#", "'#'")?;
+                    Ok(amphipod)
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e: ParseError| e.at_line(i + 1))?;
+
+            rows.push(row);
+        }
+
+        // Every room must be filled with its amphipods eventually.
+        for amphipod in species.chars() {
+            let count = corridor
+                .iter()
+                .chain(rows.iter().flatten())
+                .filter(|c| **c == amphipod)
+                .count();
+
+            if count != rows.len() {
+                return Err(ParseError::new(
+                    1,
+                    format!("{} amphipods of type '{}'", rows.len(), amphipod),
+                    count.to_string(),
+                ));
+            }
+        }
+
+        Ok(Self {
+            corridor,
+            doors,
+            rows,
+        })
+    }
+}
+
+// Rooms are indexed from the door (slot 0) to the back wall,
+// room `i` has its door at `doors[i]` in the corridor. The
+// doors are shared between all states of a search.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct State {
+    corridor: Vec<char>,
+    rooms: Vec<Vec<char>>,
+    locks: Vec<Vec<bool>>,
+    doors: Rc<[usize]>,
 }
 
 type MoveOut = ((usize, usize), usize);
 type MoveIn = (usize, (usize, usize));
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Move {
+    Out(MoveOut),
+    In(MoveIn),
+}
+
 impl State {
-    const ROOM_MAP: [char; 4] = ['A', 'B', 'C', 'D'];
-    const CORRIDOR_SLOTS: [usize; 7] = [0, 1, 3, 5, 7, 9, 10];
+    // A burrow with the puzzle's layout: the doors
+    // are at every second slot, starting at slot 2.
+    fn new<R: Into<Vec<char>>>(
+        corridor: impl Into<Vec<char>>,
+        rooms: impl IntoIterator<Item = R>,
+    ) -> Self {
+        let rooms = rooms.into_iter().map(Into::into).collect::<Vec<_>>();
+        let doors = (0..rooms.len()).map(|room| (room + 1) * 2).collect();
+
+        Self::with_doors(corridor.into(), rooms, doors)
+    }
+
+    fn with_doors(corridor: Vec<char>, rooms: Vec<Vec<char>>, doors: Vec<usize>) -> Self {
+        let locks = rooms.iter().map(|room| vec![false; room.len()]).collect();
 
-    fn new(corridor: [char; 11], rooms: [[char; 4]; 4]) -> Self {
         Self {
             corridor,
             rooms,
-            locks: [[false; 4]; 4],
+            locks,
+            doors: doors.into(),
         }
     }
 
+    // The amphipod type living in the given room.
+    fn species(room: usize) -> char {
+        (b'A' + room as u8) as char
+    }
+
     fn is_room_solved(&self, room: usize) -> bool {
-        self.rooms[room].iter().all(|e| *e == Self::ROOM_MAP[room])
+        self.rooms[room].iter().all(|e| *e == Self::species(room))
     }
 
     fn is_room_full(&self, room: usize) -> bool {
@@ -62,44 +277,92 @@ impl State {
     }
 
     fn move_out(&self, ((room, slot), to): MoveOut) -> Self {
-        let mut res = *self;
+        let mut res = self.clone();
         res.corridor[to] = self.rooms[room][slot];
         res.rooms[room][slot] = '.';
         res
     }
 
     fn move_in(&self, (from, (room, slot)): MoveIn) -> Self {
-        let mut res = *self;
+        let mut res = self.clone();
         res.corridor[from] = '.';
         res.rooms[room][slot] = self.corridor[from];
         res.locks[room][slot] = true;
         res
     }
 
-    fn possible_out_moves(&self) -> Vec<(MoveOut, usize)> {
-        let mut moves_out = vec![];
-
-        if let Some(mvs) = self.possible_moves_from_room(0) {
-            moves_out.extend(mvs)
-        }
-        if let Some(mvs) = self.possible_moves_from_room(1) {
-            moves_out.extend(mvs)
-        }
-        if let Some(mvs) = self.possible_moves_from_room(2) {
-            moves_out.extend(mvs)
-        }
-        if let Some(mvs) = self.possible_moves_from_room(3) {
-            moves_out.extend(mvs)
+    fn apply(&self, mv: Move) -> Self {
+        match mv {
+            Move::Out(mv) => self.move_out(mv),
+            Move::In(mv) => self.move_in(mv),
         }
+    }
 
-        moves_out
+    fn possible_moves(&self) -> Vec<(Move, usize)> {
+        let moves_out = self
+            .possible_out_moves()
+            .into_iter()
+            .map(|(mv, cost)| (Move::Out(mv), cost));
+        let moves_in = self
+            .possible_in_moves()
+            .into_iter()
+            .map(|(mv, cost)| (Move::In(mv), cost));
+
+        moves_out.chain(moves_in).collect()
     }
 
-    fn possible_in_moves(&self) -> Vec<(MoveIn, usize)> {
-        Self::CORRIDOR_SLOTS
+    // Lower bound for the energy needed to solve the burrow: every
+    // amphipod walks to the top slot of its room as if the corridor
+    // was empty. Amphipods blocking others in their own room need
+    // to step out into the corridor and back in.
+    fn heuristic(&self) -> usize {
+        let corridor = self
+            .corridor
             .iter()
-            .filter(|corridor| self.corridor[**corridor] != '.')
-            .filter_map(|corridor| self.possible_move_to_room(*corridor))
+            .enumerate()
+            .filter(|(_, e)| **e != '.')
+            .map(|(slot, e)| {
+                let home = self.doors[(*e as u8 - b'A') as usize];
+                self.cost(*e) * (home.abs_diff(slot) + 1)
+            });
+
+        let rooms = self.rooms.iter().enumerate().flat_map(|(room, slots)| {
+            slots
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| **e != '.')
+                .map(move |(slot, e)| {
+                    let home = (*e as u8 - b'A') as usize;
+                    let steps = if home != room {
+                        slot + 1 + self.doors[room].abs_diff(self.doors[home]) + 1
+                    } else if slots[slot..].iter().any(|e| *e != Self::species(room)) {
+                        slot + 1 + 2 + 1
+                    } else {
+                        0
+                    };
+                    self.cost(*e) * steps
+                })
+        });
+
+        corridor.chain(rooms).sum()
+    }
+
+    // Amphipods never stop directly outside of a room.
+    fn corridor_slots(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.corridor.len()).filter(|slot| !self.doors.contains(slot))
+    }
+
+    fn possible_out_moves(&self) -> Vec<(MoveOut, usize)> {
+        (0..self.rooms.len())
+            .filter_map(|room| self.possible_moves_from_room(room))
+            .flatten()
+            .collect()
+    }
+
+    fn possible_in_moves(&self) -> Vec<(MoveIn, usize)> {
+        self.corridor_slots()
+            .filter(|corridor| self.corridor[*corridor] != '.')
+            .filter_map(|corridor| self.possible_move_to_room(corridor))
             .collect::<Vec<_>>()
     }
 
@@ -112,7 +375,7 @@ impl State {
         }
 
         // is the corridor free up until the room?
-        let is_valid_move = if corridor < ((room + 1) * 2) {
+        let is_valid_move = if corridor < self.doors[room] {
             self.is_valid_move(room, corridor + 1)
         } else {
             self.is_valid_move(room, corridor - 1)
@@ -124,7 +387,7 @@ impl State {
 
         if !self.rooms[room]
             .iter()
-            .all(|e| *e == '.' || *e == Self::ROOM_MAP[room])
+            .all(|e| *e == '.' || *e == Self::species(room))
         {
             return None;
         }
@@ -137,7 +400,7 @@ impl State {
             .unwrap_or(self.rooms[room].len())
             - 1;
 
-        let cost = self.cost(element) * Self::distance(((room, slot), corridor));
+        let cost = self.cost(element) * self.distance(((room, slot), corridor));
 
         Some(((corridor, (room, slot)), cost))
     }
@@ -157,45 +420,44 @@ impl State {
         // room, we don't need to move anything out.
         if self.rooms[room][slot..]
             .iter()
-            .all(|e| *e == Self::ROOM_MAP[room])
+            .all(|e| *e == Self::species(room))
         {
             return None;
         }
 
         // create all valid moves based on the current state
-        let moves = Self::CORRIDOR_SLOTS
-            .iter()
-            .map(|to| ((room, slot), *to))
+        let moves = self
+            .corridor_slots()
+            .map(|to| ((room, slot), to))
             .filter(|((room, slot), _)| !self.locks[*room][*slot])
             .filter(|((room, _), corridor)| self.is_valid_move(*room, *corridor))
             .map(|mv @ ((room, slot), _)| {
-                (mv, self.cost(self.rooms[room][slot]) * Self::distance(mv))
+                (mv, self.cost(self.rooms[room][slot]) * self.distance(mv))
             })
             .collect::<Vec<_>>();
 
         Some(moves)
     }
 
-    fn distance(((room, slot), corridor): MoveOut) -> usize {
-        ((room + 1) * 2).abs_diff(corridor) + 1 + slot
+    fn distance(&self, ((room, slot), corridor): MoveOut) -> usize {
+        self.doors[room].abs_diff(corridor) + 1 + slot
     }
 
+    // Each amphipod type needs ten times the energy of the previous one.
     fn cost(&self, e: char) -> usize {
         match e {
-            'A' => 1,
-            'B' => 10,
-            'C' => 100,
-            'D' => 1000,
+            'A'..='Z' => 10_usize.pow((e as u8 - b'A') as u32),
             _ => 0,
         }
     }
 
+    // Drawn to stderr to keep stdout machine-readable.
     fn draw(&self) {
-        println!("{}", self);
+        eprintln!("{}", self);
     }
 
     fn is_valid_move(&self, room: usize, corridor: usize) -> bool {
-        let top = (room + 1) * 2;
+        let top = self.doors[room];
         let lo = corridor.min(top);
         let hi = corridor.max(top);
 
@@ -206,54 +468,97 @@ impl State {
 impl Display for State {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}", self.corridor.iter().collect::<String>())?;
-        writeln!(
-            f,
-            "  {} {} {} {}",
-            self.rooms[0][0], self.rooms[1][0], self.rooms[2][0], self.rooms[3][0]
-        )?;
-        writeln!(
-            f,
-            "  {} {} {} {}",
-            self.rooms[0][1], self.rooms[1][1], self.rooms[2][1], self.rooms[3][1]
-        )?;
+
+        let depth = self.rooms.iter().map(Vec::len).max().unwrap_or(0);
+        let width = self.doors.iter().max().map_or(0, |door| door + 1);
+
+        for slot in 0..depth {
+            let mut line = vec![' '; width];
+            for (room, door) in self.doors.iter().enumerate() {
+                line[*door] = self.rooms[room][slot];
+            }
+            writeln!(f, "{}", line.iter().collect::<String>())?;
+        }
 
         Ok(())
     }
 }
 
-fn search(state: State) -> usize {
-    let mut states = HashMap::new();
-    simulate(state, &mut states)
-}
+// A* from the given state to the solved burrow. Returns the
+// minimal energy and the moves with their individual cost.
+fn solve(state: State) -> Option<(usize, Vec<(Move, usize)>)> {
+    let mut queue = BinaryHeap::new();
+    let mut energies = HashMap::new();
+    let mut parents = HashMap::<State, (State, Move, usize)>::new();
 
-fn simulate(state: State, states: &mut HashMap<State, usize>) -> usize {
-    if state.is_solved() {
-        return 0;
-    }
+    energies.insert(state.clone(), 0);
+    queue.push(Reverse((state.heuristic(), 0, state)));
 
-    if let Some(cost) = states.get(&state) {
-        return *cost;
-    }
+    while let Some(Reverse((_, energy, state))) = queue.pop() {
+        if state.is_solved() {
+            let mut moves = vec![];
+            let mut current = &state;
 
-    let mut costs = vec![];
+            while let Some((parent, mv, cost)) = parents.get(current) {
+                moves.push((*mv, *cost));
+                current = parent;
+            }
+            moves.reverse();
 
-    for (mv, cost) in state.possible_out_moves() {
-        let new_state = state.move_out(mv);
-        let current_cost = simulate(new_state, states);
-        costs.push(current_cost.saturating_add(cost));
-    }
+            return Some((energy, moves));
+        }
+
+        // A cheaper path to this state has been found since it was queued.
+        if energies.get(&state).is_some_and(|e| *e < energy) {
+            continue;
+        }
 
-    for (mv, cost) in state.possible_in_moves() {
-        let new_state = state.move_in(mv);
-        let current_cost = simulate(new_state, states);
-        costs.push(current_cost.saturating_add(cost));
+        for (mv, cost) in state.possible_moves() {
+            let next = state.apply(mv);
+            let next_energy = energy + cost;
+
+            if energies.get(&next).is_none_or(|e| next_energy < *e) {
+                energies.insert(next.clone(), next_energy);
+                parents.insert(next.clone(), (state.clone(), mv, cost));
+                queue.push(Reverse((next_energy + next.heuristic(), next_energy, next)));
+            }
+        }
     }
 
-    let local_min = costs.iter().copied().min().unwrap_or(usize::MAX);
+    None
+}
+
+fn unorganized() -> Error {
+    Error::NoSolution("the amphipods cannot be organized".into())
+}
 
-    states.insert(state, local_min);
+// Draws every burrow along the cheapest solution of the
+// given part together with the energy spent so far.
+pub fn trace(solutions: &Solutions, part: Part) {
+    let organized = match part {
+        Part::One => &solutions.folded,
+        Part::Two => &solutions.unfolded,
+    };
+    let moves = &organized.moves;
+    let costs = std::iter::once(0).chain(moves.iter().map(|(_, cost)| *cost));
+
+    let mut energy = 0;
+    for (state, cost) in replay(organized.start.clone(), moves).iter().zip(costs) {
+        energy += cost;
+        eprintln!("Energy: {}", energy);
+        state.draw();
+    }
+}
 
-    local_min
+// All intermediate burrows when applying the moves to the given
+// state, starting with the state itself. Each one can be printed
+// via `draw` to follow the solution.
+fn replay(state: State, moves: &[(Move, usize)]) -> Vec<State> {
+    moves.iter().fold(vec![state], |mut states, (mv, _)| {
+        let next = states[states.len() - 1].apply(*mv);
+        states.push(next);
+        states
+    })
 }
 
 #[cfg(test)]
@@ -261,13 +566,205 @@ mod tests {
     use test::Bencher;
 
     use super::*;
+    use crate::answers::expected;
+
+    // The least energy needed, `None` if the burrow cannot be organized.
+    fn search(state: State) -> Option<usize> {
+        solve(state).map(|(energy, _)| energy)
+    }
+
+    const INPUT: &str = "\
+#############
+#...........#
+###B#C#B#D###
+  #A#D#C#A#
+  #########";
+
+    #[test]
+    fn test_parse() {
+        let burrow = INPUT.parse::<Burrow>().unwrap();
+
+        assert_eq!(
+            burrow,
+            Burrow {
+                corridor: vec!['.'; 11],
+                doors: vec![2, 4, 6, 8],
+                rows: vec![vec!['B', 'C', 'B', 'D'], vec!['A', 'D', 'C', 'A']],
+            }
+        );
+
+        assert!(
+            burrow.folded()
+                == State::new(['.'; 11], [['B', 'A'], ['C', 'D'], ['B', 'C'], ['D', 'A']],)
+        );
+
+        assert!(
+            burrow.unfolded().unwrap()
+                == State::new(
+                    ['.'; 11],
+                    [
+                        ['B', 'D', 'D', 'A'],
+                        ['C', 'C', 'B', 'D'],
+                        ['B', 'B', 'A', 'C'],
+                        ['D', 'A', 'C', 'A'],
+                    ],
+                )
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let e = INPUT
+            .replace("###B#C", "###B#E")
+            .parse::<Burrow>()
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 6: expected '.' or an amphipod, found 'E'"
+        );
+
+        let e = INPUT
+            .replace("  #A#D#C#A#", "  #A#D#C#A")
+            .parse::<Burrow>()
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 4, column 11: expected '#', found end of line"
+        );
+
+        let e = INPUT
+            .replace("#B#D###", "#B#B###")
+            .parse::<Burrow>()
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 1: expected 2 amphipods of type 'B', found 3"
+        );
+
+        // an amphipod above an empty slot used to underflow the slot
+        let input = "#############\n#A..........#\n###A#B#C#D###\n  #.#B#C#D#\n  #########\n";
+        let e = Day23::parse(input).err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "parse error in line 4, column 4: expected an amphipod, found '.'"
+        );
+
+        let burrow = input
+            .replace("###A#", "###.#")
+            .replace("  #.#B", "  #A#B")
+            .parse::<Burrow>()
+            .unwrap();
+        assert_eq!(burrow.rows[0][0], '.');
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        let burrow = "\
+#######
+#.....#
+###B#A###
+  #A#B#
+  #####"
+            .parse::<Burrow>()
+            .unwrap();
+
+        assert_eq!(burrow.corridor.len(), 5);
+        assert_eq!(burrow.doors, vec![2, 4]);
+        assert_eq!(burrow.rows.len(), 2);
+
+        let e = "#####\n#...#\n###A#A###".parse::<Burrow>().err().unwrap();
+        assert_eq!(e.to_string(), "line 3, column 6: expected '#', found 'A'");
+    }
+
+    #[test]
+    fn test_more_species() {
+        let burrow = "\
+#################
+#...............#
+###A#B#C#D#F#E###
+  ###########"
+            .parse::<Burrow>()
+            .unwrap();
+
+        assert_eq!(burrow.doors, vec![2, 4, 6, 8, 10, 12]);
+        assert_eq!(search(burrow.folded()), Some(460000));
+    }
+
+    #[test]
+    fn test_solve() {
+        let state = INPUT.parse::<Burrow>().unwrap().folded();
+        let (energy, moves) = solve(state.clone()).unwrap();
+
+        assert_eq!(energy, 12521);
+        assert_eq!(moves.iter().map(|(_, cost)| cost).sum::<usize>(), energy);
+
+        let states = replay(state, &moves);
+
+        assert_eq!(states.len(), moves.len() + 1);
+        assert!(states[states.len() - 1].is_solved());
+        assert_eq!(
+            states[states.len() - 1].to_string(),
+            "\
+...........
+  A B C D
+  A B C D
+"
+        );
+    }
+
+    #[test]
+    fn test_heuristic() {
+        let state = INPUT.parse::<Burrow>().unwrap().folded();
+        // Both B and the top C need 4 steps, the bottom D 7 steps
+        // and the bottom A 9 steps. The top D is at home but has
+        // to make way for the A below: 4 steps.
+        assert_eq!(state.heuristic(), 40 + 40 + 400 + 7000 + 9 + 4000);
+
+        assert_eq!(
+            search(state.clone()),
+            solve(state).map(|(energy, _)| energy)
+        );
+    }
+
+    #[test]
+    fn test_part2() {
+        let burrow = INPUT.parse::<Burrow>().unwrap();
+
+        assert_eq!(search(burrow.unfolded().unwrap()), Some(44169));
+    }
+
+    #[test]
+    fn test_no_solution() {
+        // The only free corridor cell is needed by both amphipods.
+        let burrow = "#####\n#...#\n#B#A#\n#####".parse::<Burrow>().unwrap();
+        assert_eq!(search(burrow.folded()), None);
+
+        let e = Day23::parse("#####\n#...#\n#B#A#\n#####").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "no solution: the amphipods cannot be organized"
+        );
+
+        let e = Day23::parse("#######\n#.....#\n###B#A###\n  #A#B#\n  #####")
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "no solution: only burrows with 4 rooms can be unfolded, found 2"
+        );
+    }
 
     #[test]
     fn test_steps() {
-        assert_eq!(State::distance(((0, 0), 1)), 2);
-        assert_eq!(State::distance(((0, 0), 3)), 2);
-        assert_eq!(State::distance(((0, 0), 10)), 9);
-        assert_eq!(State::distance(((3, 1), 0)), 10);
+        let state = State::new(['.'; 11], [['A', 'A']; 4]);
+
+        assert_eq!(state.distance(((0, 0), 1)), 2);
+        assert_eq!(state.distance(((0, 0), 3)), 2);
+        assert_eq!(state.distance(((0, 0), 10)), 9);
+        assert_eq!(state.distance(((3, 1), 0)), 10);
     }
 
     #[test]
@@ -511,38 +1008,38 @@ mod tests {
                 ['D', 'A', 'D', 'D'],
             ],
         );
-        // let state = State::new(['.'; 11], [['A', 'B'], ['D', 'C'], ['A', 'D'], ['B', 'C']]);
 
-        assert_eq!(search(state), 12521);
+        assert_eq!(search(state), Some(12521));
+
+        let state = State::new(['.'; 11], [['B', 'A'], ['C', 'D'], ['B', 'C'], ['D', 'A']]);
+
+        assert_eq!(search(state), Some(12521));
     }
 
     #[bench]
     fn bench_part1(b: &mut Bencher) {
-        let input1 = State::new(
-            ['.'; 11],
-            [
-                ['A', 'B', 'A', 'A'],
-                ['D', 'C', 'B', 'B'],
-                ['A', 'D', 'C', 'C'],
-                ['B', 'C', 'D', 'D'],
-            ],
-        );
+        let input1 = std::fs::read_to_string("input/d23.txt")
+            .expect("file not found")
+            .parse::<Burrow>()
+            .unwrap()
+            .folded();
 
-        b.iter(|| assert_eq!(search(input1), 13455));
+        let expected = expected(23, Part::One);
+
+        b.iter(|| assert_eq!(search(input1.clone()), Some(expected)));
     }
 
     #[bench]
     fn bench_part2(b: &mut Bencher) {
-        let input2 = State::new(
-            ['.'; 11],
-            [
-                ['A', 'D', 'D', 'B'],
-                ['D', 'C', 'B', 'C'],
-                ['A', 'B', 'A', 'D'],
-                ['B', 'A', 'C', 'C'],
-            ],
-        );
+        let input2 = std::fs::read_to_string("input/d23.txt")
+            .expect("file not found")
+            .parse::<Burrow>()
+            .unwrap()
+            .unfolded()
+            .unwrap();
+
+        let expected = expected(23, Part::Two);
 
-        b.iter(|| assert_eq!(search(input2), 43567));
+        b.iter(|| assert_eq!(search(input2.clone()), Some(expected)));
     }
 }
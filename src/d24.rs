@@ -1,3 +1,10 @@
+use std::{collections::HashSet, str::FromStr};
+
+use crate::{
+    error::{parse_number, Error, ParseError},
+    solution::{parse_lines, Solution},
+};
+
 // each block has the same instructions
 // but different c1, c2, and c3 values
 
@@ -77,40 +84,326 @@
 
 // The goal is to adapt the w's to have 7 push and 7 pop operations.
 
-#[rustfmt::skip]
-const C1: [i32;14] = [1,   1,  1,  1,  26,  1, 26,  26,  1,  1, 26,  26,  26, 26];
-#[rustfmt::skip]
-const C2: [i32;14] = [14, 13, 13, 12, -12, 12, -2, -11, 13, 14,  0, -12, -13, -6];
-#[rustfmt::skip]
-const C3: [i32;14] = [8,   8,  3, 10,   8,  8,  8,   5,  9,  3,  4,   9,   2,  7];
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Register {
+    W,
+    X,
+    Y,
+    Z,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Register(Register),
+    Number(i64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    Inp(Register),
+    Add(Register, Operand),
+    Mul(Register, Operand),
+    Div(Register, Operand),
+    Mod(Register, Operand),
+    Eql(Register, Operand),
+}
+
+// The constants of a single block, see above.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Block {
+    c1: i32,
+    c2: i32,
+    c3: i32,
+}
+
+// Programs which do not follow the MONAD block structure
+// are solved by the more general `search` below.
+pub struct Monad {
+    program: Vec<Instruction>,
+    blocks: Option<Vec<Block>>,
+    largest: usize,
+    smallest: usize,
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Input = Monad;
+
+    const SOLVES_IN_PARSE: bool = true;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let program = parse_lines(input)?;
+        let blocks = blocks(input, &program).ok();
+
+        let solve = |solve: fn(&[Block]) -> Option<Vec<i64>>, digits: &[i64]| {
+            let model = blocks
+                .as_deref()
+                .and_then(solve)
+                .or_else(|| search(&program, digits))
+                .ok_or_else(|| Error::NoSolution("the program accepts no model number".into()))?;
+
+            model_number(&model)
+        };
+
+        let largest = solve(max, &[9, 8, 7, 6, 5, 4, 3, 2, 1])?;
+        let smallest = solve(min, &[1, 2, 3, 4, 5, 6, 7, 8, 9])?;
+
+        Ok(Monad {
+            program,
+            blocks,
+            largest,
+            smallest,
+        })
+    }
+
+    fn part1(monad: &Self::Input) -> usize {
+        monad.largest
+    }
+
+    fn part2(monad: &Self::Input) -> usize {
+        monad.smallest
+    }
+}
+
+impl FromStr for Register {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "w" => Ok(Register::W),
+            "x" => Ok(Register::X),
+            "y" => Ok(Register::Y),
+            "z" => Ok(Register::Z),
+            _ => Err(ParseError::at(s, s, "a register")),
+        }
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut tokens = line.split(' ');
+        let mut next = |expected: &str| {
+            tokens
+                .next()
+                .filter(|token| !token.is_empty())
+                .ok_or_else(|| ParseError::at(line, &line[line.len()..], expected))
+        };
+
+        let op = next("an instruction")?;
+        let a = next("a register")?;
+        let a = a.parse::<Register>().map_err(|e| e.within(line, a))?;
+
+        let instruction = if op == "inp" {
+            Instruction::Inp(a)
+        } else {
+            let b = next("a register or a number")?;
+            let b = match b.parse::<Register>() {
+                Ok(register) => Operand::Register(register),
+                Err(_) => Operand::Number(
+                    parse_number(line, b)
+                        .map_err(|_| ParseError::at(line, b, "a register or a number"))?,
+                ),
+            };
+
+            match op {
+                "add" => Instruction::Add(a, b),
+                "mul" => Instruction::Mul(a, b),
+                "div" => Instruction::Div(a, b),
+                "mod" => Instruction::Mod(a, b),
+                "eql" => Instruction::Eql(a, b),
+                _ => return Err(ParseError::at(line, op, "an instruction")),
+            }
+        };
+
+        match tokens.next() {
+            Some(token) => Err(ParseError::at(line, token, "end of line")),
+            None => Ok(instruction),
+        }
+    }
+}
+
+// Runs the program on the given input digits and returns the
+// registers w, x, y and z. Returns `None` if the program reads
+// more digits than given, the ALU would crash or a register
+// overflows.
+fn execute(program: &[Instruction], input: &[i64]) -> Option<[i64; 4]> {
+    run([0; 4], program, input)
+}
+
+fn run(mut registers: [i64; 4], program: &[Instruction], input: &[i64]) -> Option<[i64; 4]> {
+    let mut input = input.iter();
+
+    for instruction in program {
+        let value = |b: &Operand, registers: &[i64; 4]| match b {
+            Operand::Register(r) => registers[*r as usize],
+            Operand::Number(n) => *n,
+        };
 
-pub fn run() -> (usize, usize) {
-    (max(), min())
+        match instruction {
+            Instruction::Inp(a) => registers[*a as usize] = *input.next()?,
+            Instruction::Add(a, b) => {
+                let b = value(b, &registers);
+                registers[*a as usize] = registers[*a as usize].checked_add(b)?;
+            }
+            Instruction::Mul(a, b) => {
+                let b = value(b, &registers);
+                registers[*a as usize] = registers[*a as usize].checked_mul(b)?;
+            }
+            Instruction::Div(a, b) => {
+                let b = value(b, &registers);
+                registers[*a as usize] = registers[*a as usize].checked_div(b)?;
+            }
+            Instruction::Mod(a, b) => {
+                let b = value(b, &registers);
+                if registers[*a as usize] < 0 || b <= 0 {
+                    return None;
+                }
+                registers[*a as usize] %= b;
+            }
+            Instruction::Eql(a, b) => {
+                let b = value(b, &registers);
+                registers[*a as usize] = (registers[*a as usize] == b) as i64;
+            }
+        }
+    }
+
+    Some(registers)
+}
+
+// The instructions of every block. The constants
+// at index 4, 5 and 15 differ between the blocks.
+const BLOCK: [&str; 18] = [
+    "inp w", "mul x 0", "add x z", "mod x 26", "div z 1", "add x 0", "eql x w", "eql x 0",
+    "mul y 0", "add y 25", "mul y x", "add y 1", "mul z y", "mul y 0", "add y w", "add y 0",
+    "mul y x", "add z y",
+];
+
+// Extracts the constants of each block from the program,
+// which has to follow the structure described above.
+fn blocks(input: &str, program: &[Instruction]) -> Result<Vec<Block>, ParseError> {
+    use Instruction::*;
+    use Operand::Number;
+    use Register::*;
+
+    let lines = input.lines().collect::<Vec<_>>();
+    let template = BLOCK
+        .iter()
+        .map(|line| line.parse::<Instruction>().unwrap())
+        .collect::<Vec<_>>();
+
+    // The number of values on the arithmetic stack.
+    let mut depth = 0;
+
+    let blocks = program
+        .chunks(BLOCK.len())
+        .enumerate()
+        .map(|(block, instructions)| {
+            let offset = block * BLOCK.len();
+
+            for (i, expected) in template.iter().enumerate() {
+                let valid = match (i, instructions.get(i)) {
+                    (4, Some(Div(Z, Number(1 | 26)))) => true,
+                    (5, Some(Add(X, Number(_)))) | (15, Some(Add(Y, Number(_)))) => true,
+                    (4 | 5 | 15, _) => false,
+                    (_, instruction) => instruction == Some(expected),
+                };
+
+                if !valid {
+                    let line = lines
+                        .get(offset + i)
+                        .copied()
+                        .unwrap_or(&input[input.len()..]);
+                    let expected = match i {
+                        4 => "'div z 1' or 'div z 26'".to_string(),
+                        5 => "'add x' with a number".to_string(),
+                        15 => "'add y' with a number".to_string(),
+                        _ => format!("'{}'", BLOCK[i]),
+                    };
+                    return Err(ParseError::at(line, line, expected).at_line(offset + i + 1));
+                }
+            }
+
+            let number = |i: usize| match instructions[i] {
+                Div(_, Number(n)) | Add(_, Number(n)) => n as i32,
+                _ => unreachable!("checked above"),
+            };
+
+            let block = Block {
+                c1: number(4),
+                c2: number(5),
+                c3: number(15),
+            };
+
+            // Blocks which do not pop from the stack have to push,
+            // i.e. x = z.top() + C2 can never be equal to a digit.
+            if block.c1 == 1 && block.c2 <= 9 {
+                let line = lines[offset + 5];
+                let token = line.rsplit(' ').next().unwrap_or(line);
+                return Err(
+                    ParseError::at(line, token, "a number greater than 9").at_line(offset + 6)
+                );
+            }
+
+            if block.c1 == 1 {
+                depth += 1;
+            } else if depth == 0 {
+                let line = lines[offset + 4];
+                return Err(ParseError::at(line, line, "'div z 1'").at_line(offset + 5));
+            } else {
+                depth -= 1;
+            }
+
+            // Blocks which pop from the stack have to lower
+            // the value pushed by the matching block.
+            if block.c1 == 26 && block.c2 > 0 {
+                let line = lines[offset + 5];
+                let token = line.rsplit(' ').next().unwrap_or(line);
+                return Err(
+                    ParseError::at(line, token, "a number not greater than 0").at_line(offset + 6)
+                );
+            }
+
+            Ok(block)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Every value pushed to the stack has to be popped again.
+    if depth > 0 {
+        let end = &input[input.len()..];
+        let expected = format!("{} more blocks with 'div z 26'", depth);
+        return Err(ParseError::at(end, end, expected).at_line(lines.len() + 1));
+    }
+
+    Ok(blocks)
 }
 
 // Run the puzzle program for the given input
 // and return the arithmetic stack.
 //
 // The input is valid if the stack is empty.
-fn check(input: [i32; 14]) -> Vec<i32> {
+fn check(blocks: &[Block], input: &[i32]) -> Vec<i32> {
     let mut z = vec![];
 
-    for op in 0..14 {
-        let w = input[op];
+    for (block, w) in blocks.iter().zip(input) {
         // x = z.top() + C2
-        let x = if op == 0 { 0 } else { z[z.len() - 1] } + C2[op];
-        if C1[op] == 26 {
+        let x = z.last().copied().unwrap_or(0) + block.c2;
+        if block.c1 == 26 {
             z.pop();
         }
-        if x != w {
-            z.push(w + C3[op]);
+        if x != *w {
+            z.push(w + block.c3);
         }
     }
 
     z
 }
 
-fn max() -> usize {
+// Both return the digits of the model number, or `None` if the
+// blocks do not balance or no digits satisfy a pair of blocks.
+fn max(blocks: &[Block]) -> Option<Vec<i64>> {
     let mut z = vec![];
     let mut res = vec![];
 
@@ -118,39 +411,33 @@ fn max() -> usize {
     // for each op and adapt them if necessary
     let w = 9;
 
-    (0..14).for_each(|op| {
-        if C1[op] == 1 {
+    for block in blocks {
+        if block.c1 == 1 {
             // type 1 operation
             // we just push w + C3 to the stack and
             // store w (9) as input for that op
-            z.push((res.len(), w + C3[op]));
+            z.push((res.len(), w + block.c3));
             res.push(w);
         } else {
             // type 2 operation
-            assert!(C1[op] == 26);
-            assert!(C2[op] <= 0);
-            let (j, v) = z.pop().unwrap();
+            let (j, v) = z.pop()?;
 
             // We need to make sure that w stays within its bounds.
-            if v + C2[op] > w {
+            if v + block.c2 > w {
                 // If the corresponding push operation picked a value
                 // for w that is too large, we need to adapt it now.
-                res[j] -= v + C2[op] - w;
+                res[j] -= v + block.c2 - w;
                 res.push(w);
             } else {
-                res.push(v + C2[op]);
+                res.push(v + block.c2);
             }
         }
-    });
+    }
 
-    res.iter()
-        .map(|n| n.to_string())
-        .collect::<String>()
-        .parse::<usize>()
-        .unwrap()
+    number(&z, &res)
 }
 
-fn min() -> usize {
+fn min(blocks: &[Block]) -> Option<Vec<i64>> {
     let mut z: Vec<(usize, i32)> = vec![];
     let mut res: Vec<i32> = vec![];
 
@@ -158,51 +445,472 @@ fn min() -> usize {
     // for each op and adapt them if necessary
     let w = 1;
 
-    (0..14).for_each(|op| {
-        if C1[op] == 1 {
-            z.push((res.len(), w + C3[op]));
+    for block in blocks {
+        if block.c1 == 1 {
+            z.push((res.len(), w + block.c3));
             res.push(w);
         } else {
-            let (j, v) = z.pop().unwrap();
-            if v + C2[op] <= 0 {
-                res[j] += -(v + C2[op]) + w;
+            let (j, v) = z.pop()?;
+            if v + block.c2 <= 0 {
+                res[j] += -(v + block.c2) + w;
                 res.push(w);
             } else {
-                res.push(v + C2[op]);
+                res.push(v + block.c2);
+            }
+        }
+    }
+
+    number(&z, &res)
+}
+
+// Checks the digits found by `max` or `min`, given the remaining stack.
+fn number(z: &[(usize, i32)], digits: &[i32]) -> Option<Vec<i64>> {
+    if !z.is_empty() || digits.iter().any(|d| !(1..=9).contains(d)) {
+        return None;
+    }
+
+    Some(digits.iter().map(|d| *d as i64).collect())
+}
+
+// Joins the digits, which may be too many for a number.
+fn model_number(digits: &[i64]) -> Result<usize, Error> {
+    digits
+        .iter()
+        .try_fold(0_usize, |n, d| n.checked_mul(10)?.checked_add(*d as usize))
+        .ok_or_else(|| {
+            let digits = digits.iter().map(|d| d.to_string()).collect::<String>();
+            Error::NoSolution(format!("the model number {} is too large", digits))
+        })
+}
+
+// Inclusive bounds of the values a register can hold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Range(i64, i64);
+
+impl Range {
+    fn contains(&self, n: i64) -> bool {
+        self.0 <= n && n <= self.1
+    }
+
+    // The smallest range containing all the given values.
+    fn hull(values: impl IntoIterator<Item = i64>) -> Self {
+        values
+            .into_iter()
+            .fold(Range(i64::MAX, i64::MIN), |Range(lo, hi), n| {
+                Range(lo.min(n), hi.max(n))
+            })
+    }
+}
+
+// Propagates value ranges through the program, assuming each input
+// is within the given digits. The resulting ranges contain every
+// value the registers can hold at the end of a run which does not
+// crash the ALU. Returns `None` if every run crashes.
+fn analyze(
+    mut registers: [Range; 4],
+    program: &[Instruction],
+    digits: Range,
+) -> Option<[Range; 4]> {
+    for instruction in program {
+        let value = |b: &Operand, registers: &[Range; 4]| match b {
+            Operand::Register(r) => registers[*r as usize],
+            Operand::Number(n) => Range(*n, *n),
+        };
+
+        let (a, range) = match instruction {
+            Instruction::Inp(a) => (a, digits),
+            Instruction::Add(a, b) => {
+                let (Range(a_lo, a_hi), Range(b_lo, b_hi)) =
+                    (registers[*a as usize], value(b, &registers));
+                (
+                    a,
+                    Range(a_lo.saturating_add(b_lo), a_hi.saturating_add(b_hi)),
+                )
+            }
+            Instruction::Mul(a, b) => {
+                let (Range(a_lo, a_hi), Range(b_lo, b_hi)) =
+                    (registers[*a as usize], value(b, &registers));
+                let products = [(a_lo, b_lo), (a_lo, b_hi), (a_hi, b_lo), (a_hi, b_hi)]
+                    .map(|(x, y)| x.saturating_mul(y));
+                (a, Range::hull(products))
+            }
+            Instruction::Div(a, b) => {
+                let (Range(a_lo, a_hi), Range(b_lo, b_hi)) =
+                    (registers[*a as usize], value(b, &registers));
+                // Dividing by zero crashes, the quotient is monotonic
+                // on either side of zero, so the extremes are reached
+                // at the bounds or at -1 and 1.
+                let divisors = [b_lo, b_hi, -1, 1]
+                    .into_iter()
+                    .filter(|d| *d != 0 && b_lo <= *d && *d <= b_hi)
+                    .collect::<Vec<_>>();
+                if divisors.is_empty() {
+                    return None;
+                }
+                let quotients = divisors
+                    .iter()
+                    .flat_map(|d| [a_lo.saturating_div(*d), a_hi.saturating_div(*d)]);
+                (a, Range::hull(quotients))
+            }
+            Instruction::Mod(a, b) => {
+                // Only non-negative values modulo positive ones are valid.
+                let (Range(a_lo, a_hi), Range(b_lo, b_hi)) =
+                    (registers[*a as usize], value(b, &registers));
+                let (a_lo, b_lo) = (a_lo.max(0), b_lo.max(1));
+                if a_lo > a_hi || b_lo > b_hi {
+                    return None;
+                }
+                if b_lo == b_hi && a_hi - a_lo < b_lo && a_lo % b_lo <= a_hi % b_lo {
+                    (a, Range(a_lo % b_lo, a_hi % b_lo))
+                } else {
+                    (a, Range(0, a_hi.min(b_hi - 1)))
+                }
+            }
+            Instruction::Eql(a, b) => {
+                let (Range(a_lo, a_hi), Range(b_lo, b_hi)) =
+                    (registers[*a as usize], value(b, &registers));
+                if a_lo == a_hi && b_lo == b_hi && a_lo == b_lo {
+                    (a, Range(1, 1))
+                } else if a_hi < b_lo || b_hi < a_lo {
+                    (a, Range(0, 0))
+                } else {
+                    (a, Range(0, 1))
+                }
+            }
+        };
+
+        registers[*a as usize] = range;
+    }
+
+    Some(registers)
+}
+
+// Finds the digits of the first model number accepted by an arbitrary
+// program when trying the digits in the given order at every position,
+// i.e. the largest one for descending digits. Digits are fixed one by
+// one and states from which z can no longer become 0 are skipped.
+fn search(program: &[Instruction], digits: &[i64]) -> Option<Vec<i64>> {
+    let inputs = program
+        .iter()
+        .enumerate()
+        .filter(|(_, instruction)| matches!(instruction, Instruction::Inp(_)))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+
+    let start = inputs.first().copied().unwrap_or(program.len());
+    let registers = run([0; 4], &program[..start], &[])?;
+    let range = Range::hull(digits.iter().copied());
+
+    find(
+        program,
+        &inputs,
+        registers,
+        digits,
+        range,
+        &mut HashSet::new(),
+    )
+}
+
+// `inputs` are the positions of the remaining `inp` instructions,
+// `failed` contains all states which lead to no accepted number.
+fn find(
+    program: &[Instruction],
+    inputs: &[usize],
+    registers: [i64; 4],
+    digits: &[i64],
+    range: Range,
+    failed: &mut HashSet<(usize, [i64; 4])>,
+) -> Option<Vec<i64>> {
+    let (start, rest) = match inputs.split_first() {
+        Some((start, rest)) => (*start, rest),
+        None => return (registers[3] == 0).then(Vec::new),
+    };
+
+    if failed.contains(&(inputs.len(), registers)) {
+        return None;
+    }
+
+    let feasible = analyze(registers.map(|r| Range(r, r)), &program[start..], range)
+        .is_some_and(|registers| registers[3].contains(0));
+
+    if feasible {
+        let end = rest.first().copied().unwrap_or(program.len());
+
+        for digit in digits {
+            let model = run(registers, &program[start..end], &[*digit])
+                .and_then(|next| find(program, rest, next, digits, range, failed));
+
+            if let Some(model) = model {
+                return Some([*digit].into_iter().chain(model).collect());
             }
         }
-    });
+    }
+
+    failed.insert((inputs.len(), registers));
 
-    res.iter()
-        .map(|n| n.to_string())
-        .collect::<String>()
-        .parse::<usize>()
-        .unwrap()
+    None
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{answers::expected, solution::Part};
+
+    fn input() -> Monad {
+        let input = std::fs::read_to_string("input/d24.txt").expect("file not found");
+        Day24::parse(&input).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "add x -12".parse::<Instruction>().unwrap(),
+            Instruction::Add(Register::X, Operand::Number(-12))
+        );
+        assert_eq!(
+            "eql x w".parse::<Instruction>().unwrap(),
+            Instruction::Eql(Register::X, Operand::Register(Register::W))
+        );
+        assert_eq!(
+            "inp z".parse::<Instruction>().unwrap(),
+            Instruction::Inp(Register::Z)
+        );
+
+        let monad = input();
+        assert_eq!(monad.program.len(), 14 * BLOCK.len());
+        let blocks = monad.blocks.unwrap();
+        assert_eq!(blocks.len(), 14);
+        assert_eq!(
+            blocks[4],
+            Block {
+                c1: 26,
+                c2: -12,
+                c3: 8
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let e = "sub x 1".parse::<Instruction>().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 1: expected an instruction, found 'sub'"
+        );
+
+        let e = "add v 1".parse::<Instruction>().unwrap_err();
+        assert_eq!(e.to_string(), "column 5: expected a register, found 'v'");
+
+        let e = "mul x".parse::<Instruction>().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "column 6: expected a register or a number, found end of line"
+        );
+
+        let e = "mod x 26 1".parse::<Instruction>().unwrap_err();
+        assert_eq!(e.to_string(), "column 10: expected end of line, found '1'");
+
+        let e = "inp w 5".parse::<Instruction>().unwrap_err();
+        assert_eq!(e.to_string(), "column 7: expected end of line, found '5'");
+    }
+
+    #[test]
+    fn test_blocks_error() {
+        let input = std::fs::read_to_string("input/d24.txt").expect("file not found");
+        let blocks = |input: &str| blocks(input, &parse_lines(input).unwrap());
+
+        let e = blocks(&input.replacen("div z 1\n", "div z 2\n", 1)).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 5, column 1: expected 'div z 1' or 'div z 26', found 'div z 2'"
+        );
+
+        let e = blocks(&input.replacen("add x 14", "add x 4", 1)).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 6, column 7: expected a number greater than 9, found '4'"
+        );
+
+        let e = blocks(&input.replacen("mul y x", "mul y w", 1)).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 11, column 1: expected 'mul y x', found 'mul y w'"
+        );
+
+        let e = blocks(input.trim_end().rsplit_once('\n').unwrap().0).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 252, column 1: expected 'add z y', found end of line"
+        );
+
+        let e = blocks(&input.replacen("add x -12", "add x 3", 1)).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 78, column 7: expected a number not greater than 0, found '3'"
+        );
+
+        let e = blocks(&input.replacen("div z 1\n", "div z 26\n", 1)).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 5, column 1: expected 'div z 1', found 'div z 26'"
+        );
+
+        let e = blocks(&input.replacen("div z 26\nadd x -12", "div z 1\nadd x 12", 1)).unwrap_err();
+        assert!(e
+            .to_string()
+            .ends_with("expected 2 more blocks with 'div z 26', found end of line"));
+    }
+
+    #[test]
+    fn test_unbalanced_program() {
+        // Popping from the empty stack leaves z unchanged,
+        // so the general search still finds a number.
+        let input = std::fs::read_to_string("input/d24.txt")
+            .expect("file not found")
+            .replacen("div z 1\n", "div z 26\n", 1);
+        let monad = Day24::parse(&input).unwrap();
+
+        assert!(monad.blocks.is_none());
+        assert_eq!(Day24::part1(&monad), expected(24, Part::One));
+        assert_eq!(Day24::part2(&monad), expected(24, Part::Two));
+    }
+
+    #[test]
+    fn test_execute() {
+        let program = parse_lines::<Instruction>("inp z\ninp x\nmul z 3\neql z x").unwrap();
+        assert_eq!(execute(&program, &[2, 6]).unwrap()[3], 1);
+        assert_eq!(execute(&program, &[2, 7]).unwrap()[3], 0);
+        assert_eq!(execute(&program, &[2]), None);
+
+        let program = parse_lines::<Instruction>(
+            "inp w\nadd z w\nmod z 2\ndiv w 2\nadd y w\nmod y 2\ndiv w 2\nadd x w\nmod x 2\ndiv w 2\nmod w 2",
+        )
+        .unwrap();
+        assert_eq!(execute(&program, &[13]), Some([1, 1, 0, 1]));
+
+        let program = parse_lines::<Instruction>("inp x\nmod x 2").unwrap();
+        assert_eq!(execute(&program, &[-1]), None);
+
+        let program = parse_lines::<Instruction>("inp w\nmul w 999999999999\nmul w w").unwrap();
+        assert_eq!(execute(&program, &[9]), None);
+        let e = Day24::parse("inp w\nmul w 999999999999\nmul w w")
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "no solution: the program accepts no model number"
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        let program = parse_lines::<Instruction>(
+            "inp w\nadd z w\nmul z 26\nadd z -3\ninp x\neql x 10\nmod z 7\ndiv y 0",
+        )
+        .unwrap();
+        let registers = [Range(0, 0); 4];
+
+        assert_eq!(
+            analyze(registers, &program[..7], Range(1, 9)),
+            Some([Range(1, 9), Range(0, 0), Range(0, 0), Range(0, 6)])
+        );
+        assert_eq!(
+            analyze(registers, &program[..4], Range(1, 2)),
+            Some([Range(1, 2), Range(0, 0), Range(0, 0), Range(23, 49)])
+        );
+        assert_eq!(analyze(registers, &program, Range(1, 9)), None);
+    }
+
+    #[test]
+    fn test_search() {
+        // Accepts all numbers where the second digit is
+        // the first one plus two.
+        let program =
+            parse_lines::<Instruction>("inp w\ninp x\nadd z w\nmul z -1\nadd z x\nadd z -2")
+                .unwrap();
+
+        assert_eq!(
+            search(&program, &[9, 8, 7, 6, 5, 4, 3, 2, 1]),
+            Some(vec![7, 9])
+        );
+        assert_eq!(
+            search(&program, &[1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            Some(vec![1, 3])
+        );
+
+        let program = parse_lines::<Instruction>("inp w\nadd z 1").unwrap();
+        assert_eq!(search(&program, &[1, 2, 3, 4, 5, 6, 7, 8, 9]), None);
+
+        // twenty digits do not fit into a usize
+        let e = Day24::parse(&"inp w\n".repeat(20)).err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "no solution: the model number 99999999999999999999 is too large"
+        );
+
+        let e = Day24::parse("inp w\nadd z 1").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "no solution: the program accepts no model number"
+        );
+    }
+
+    #[test]
+    fn test_search_program() {
+        // An equivalent program which does not follow the block
+        // structure, i.e. is solved by the general search.
+        let input = std::fs::read_to_string("input/d24.txt")
+            .expect("file not found")
+            .replace("mul x 0\nadd x z", "mul x 0\nadd x 0\nadd x z");
+        let monad = Day24::parse(&input).unwrap();
+
+        assert!(monad.blocks.is_none());
+        assert_eq!(Day24::part1(&monad), expected(24, Part::One));
+        assert_eq!(Day24::part2(&monad), expected(24, Part::Two));
+    }
 
     #[test]
     fn test_check_valid() {
         let max = [7, 9, 9, 9, 7, 3, 9, 1, 9, 6, 9, 6, 4, 9];
-        assert_eq!(check(max), vec![]);
+        assert_eq!(check(input().blocks.as_ref().unwrap(), &max), vec![]);
     }
 
     #[test]
     fn test_check_invalid() {
         let max = [4, 2, 4, 2, 4, 2, 4, 2, 4, 2, 4, 2, 4, 2];
-        assert_eq!(check(max), vec![12, 10, 7, 12, 7, 13, 9]); // invalid
+        assert_eq!(
+            check(input().blocks.as_ref().unwrap(), &max),
+            vec![12, 10, 7, 12, 7, 13, 9]
+        );
+        // invalid
+    }
+
+    #[test]
+    fn test_check_program() {
+        let monad = input();
+        let blocks = monad.blocks.as_ref().unwrap();
+
+        for digits in [max(blocks).unwrap(), min(blocks).unwrap()] {
+            assert_eq!(execute(&monad.program, &digits).unwrap()[3], 0);
+        }
+
+        let invalid = [4, 2, 4, 2, 4, 2, 4, 2, 4, 2, 4, 2, 4, 2];
+        assert_ne!(execute(&monad.program, &invalid).unwrap()[3], 0);
     }
 
     #[test]
     fn test_part_1() {
-        assert_eq!(max(), 79997391969649);
+        assert_eq!(
+            model_number(&max(input().blocks.as_ref().unwrap()).unwrap()).unwrap(),
+            expected(24, Part::One)
+        );
     }
 
     #[test]
     fn test_part_2() {
-        assert_eq!(min(), 16931171414113);
+        assert_eq!(
+            model_number(&min(input().blocks.as_ref().unwrap()).unwrap()).unwrap(),
+            expected(24, Part::Two)
+        );
     }
 }
@@ -3,7 +3,12 @@ use std::{
     str::FromStr,
 };
 
-#[derive(Debug, PartialEq, Eq)]
+use crate::{
+    error::{Error, ParseError},
+    solution::{parse_lines, Solution},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Field {
     East,
     South,
@@ -35,14 +40,26 @@ impl std::fmt::Display for Field {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Line(Vec<Field>);
 
 impl FromStr for Line {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Line(s.trim().chars().map(Field::from).collect::<Vec<_>>()))
+        let line = s.trim();
+
+        line.char_indices()
+            .map(|(i, c)| match c {
+                '>' | 'v' | '.' => Ok(Field::from(c)),
+                _ => Err(ParseError::at(
+                    s,
+                    &line[i..i + c.len_utf8()],
+                    "'>', 'v' or '.'",
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Line)
     }
 }
 
@@ -60,8 +77,23 @@ impl DerefMut for Line {
     }
 }
 
-pub fn run(lines: Vec<Line>) -> (usize, usize) {
-    (part1(lines), 0)
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input = Vec<Line>;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input)
+    }
+
+    fn part1(lines: &Self::Input) -> usize {
+        part1(lines.clone())
+    }
+
+    // There is no second puzzle on the last day.
+    fn part2(_: &Self::Input) -> usize {
+        0
+    }
 }
 
 fn part1(mut lines: Vec<Line>) -> usize {
@@ -161,6 +193,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_error() {
+        let e = "v..<>".parse::<Line>().err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "column 4: expected '>', 'v' or '.', found '<'"
+        );
+    }
+
     #[test]
     fn test_part1() {
         assert_eq!(part1(parse(INPUT)), 58);
@@ -0,0 +1,218 @@
+use std::{fmt::Display, num::ParseIntError, str::FromStr};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Parse(ParseError),
+    Mismatch(usize),
+    // The input is well-formed, but has no answer.
+    NoSolution(String),
+}
+
+impl Error {
+    pub fn in_day(self, day: u32) -> Self {
+        match self {
+            Self::Parse(e) => Self::Parse(e.in_day(day)),
+            e => e,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Parse(e) => write!(f, "parse error in {}", e),
+            Self::Mismatch(1) => write!(f, "1 answer does not match"),
+            Self::Mismatch(n) => write!(f, "{} answers do not match", n),
+            Self::NoSolution(reason) => write!(f, "no solution: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+// Points at the offending token of a puzzle input.
+// Lines and columns start at 1, the column counts
+// characters within the line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    day: Option<u32>,
+    line: Option<usize>,
+    column: usize,
+    expected: String,
+    found: String,
+}
+
+impl ParseError {
+    pub fn new(column: usize, expected: impl Into<String>, found: impl Into<String>) -> Self {
+        Self {
+            day: None,
+            line: None,
+            column,
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+
+    // `token` must be a sub-slice of `src`,
+    // the column is derived from its offset.
+    pub fn at(src: &str, token: &str, expected: impl Into<String>) -> Self {
+        let found = if token.is_empty() {
+            "end of line".to_string()
+        } else {
+            format!("'{}'", token)
+        };
+
+        Self::new(column(src, token), expected, found)
+    }
+
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    pub fn in_day(mut self, day: u32) -> Self {
+        self.day = Some(day);
+        self
+    }
+
+    // Moves an error reported for `part` to its position
+    // within `src`, e.g. from a single token to its line.
+    pub fn within(mut self, src: &str, part: &str) -> Self {
+        self.column += column(src, part) - 1;
+        self
+    }
+
+    // Moves an error reported for a block of lines
+    // to its position within the whole input.
+    pub fn after_lines(mut self, lines: usize) -> Self {
+        self.line = Some(self.line.unwrap_or(1) + lines);
+        self
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {:02}, ", day)?;
+        }
+        if let Some(line) = self.line {
+            write!(f, "line {}, ", line)?;
+        }
+        write!(
+            f,
+            "column {}: expected {}, found {}",
+            self.column, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Conversion of `FromStr` errors for inputs parsed line by line.
+pub trait IntoParseError {
+    fn into_parse_error(self, line: &str) -> ParseError;
+}
+
+impl IntoParseError for ParseError {
+    fn into_parse_error(self, _: &str) -> ParseError {
+        self
+    }
+}
+
+impl IntoParseError for ParseIntError {
+    fn into_parse_error(self, line: &str) -> ParseError {
+        ParseError::at(line, line, "a number")
+    }
+}
+
+pub fn split_once<'a>(
+    src: &str,
+    s: &'a str,
+    delimiter: &str,
+) -> Result<(&'a str, &'a str), ParseError> {
+    s.split_once(delimiter)
+        .ok_or_else(|| ParseError::at(src, s, format!("'{}'", delimiter)))
+}
+
+pub fn parse_number<T: FromStr>(src: &str, token: &str) -> Result<T, ParseError> {
+    token
+        .parse::<T>()
+        .map_err(|_| ParseError::at(src, token, "a number"))
+}
+
+fn column(src: &str, part: &str) -> usize {
+    let offset = part.as_ptr() as usize - src.as_ptr() as usize;
+    debug_assert!(offset <= src.len(), "part is not a sub-slice of src");
+    src[..offset].chars().count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_at() {
+        let line = "0,9 -> 5,x";
+        let e = ParseError::at(line, &line[9..], "a number");
+
+        assert_eq!(e.column(), 10);
+        assert_eq!(e.to_string(), "column 10: expected a number, found 'x'");
+    }
+
+    #[test]
+    fn test_display() {
+        let line = "up";
+        let e = split_once(line, line, " ")
+            .unwrap_err()
+            .at_line(3)
+            .in_day(2);
+
+        assert_eq!(
+            Error::from(e).to_string(),
+            "parse error in day 02, line 3, column 1: expected ' ', found 'up'"
+        );
+    }
+
+    #[test]
+    fn test_within() {
+        let line = "0,9 -> 5,x";
+        let point = &line[7..];
+        let e = parse_number::<i32>(point, &point[2..]).unwrap_err();
+
+        assert_eq!(e.column(), 3);
+        assert_eq!(e.within(line, point).column(), 10);
+    }
+
+    #[test]
+    fn test_end_of_line() {
+        let line = "forward ";
+        let e = parse_number::<u32>(line, &line[8..]).unwrap_err();
+
+        assert_eq!(
+            e.to_string(),
+            "column 9: expected a number, found end of line"
+        );
+    }
+}
@@ -5,9 +5,17 @@
 
 extern crate test;
 
-use std::fmt::Debug;
-use std::str::FromStr;
+use std::any::Any;
+use std::io::{self, Read};
+use std::process::ExitCode;
+use std::time::Instant;
 
+use answers::Answers;
+use d16::LengthType;
+use error::Error;
+use solution::{Day, Part, Solution, DAYS};
+
+mod answers;
 mod d01;
 mod d02;
 mod d03;
@@ -33,119 +41,538 @@ mod d22;
 mod d23;
 mod d24;
 mod d25;
+mod error;
+mod parallel;
+mod solution;
+mod timing;
 
-fn main() {
-    let day = std::env::args()
-        .nth(1)
-        .map(|d| d.parse::<u32>().unwrap())
-        .unwrap();
-
-    let (res1, res2) = match day {
-        1 => d01::run(&read_file("input/d01.txt")),
-        2 => d02::run(&read_file("input/d02.txt")),
-        3 => d03::run(
-            read_file_with("input/d03.txt", |line| line.to_string())
-                .iter()
-                .map(|s| s.as_str())
-                .collect::<Vec<_>>()
-                .as_slice(),
-        ),
-        4 => d04::run(
-            read_file_with("input/d04.txt", |line| line.to_string())
-                .iter()
-                .map(|s| s.as_str())
-                .collect::<Vec<_>>()
-                .as_slice(),
-        ),
-        5 => d05::run(&read_file("input/d05.txt")),
-        6 => d06::run(&read_file_with("input/d06.txt", d06::parse)[0]),
-        7 => d07::run(&read_file_with("input/d07.txt", d07::parse)[0]),
-        8 => d08::run(&read_file("input/d08.txt")),
-        9 => d09::run(read_file_with("input/d09.txt", d09::parse)),
-        10 => d10::run(
-            read_file_with("input/d10.txt", |line| line.to_string())
-                .iter()
-                .map(|s| s.as_str())
-                .collect::<Vec<_>>()
-                .as_slice(),
-        ),
-        11 => d11::run(
-            read_file_with("input/d11.txt", |line| line.to_string())
-                .iter()
-                .map(|s| s.as_str())
-                .collect::<Vec<_>>()
-                .as_slice(),
-        ),
-        12 => d12::run(
-            read_file_with("input/d12.txt", |line| line.to_string())
-                .iter()
-                .map(|s| s.as_str())
-                .collect::<Vec<_>>()
-                .as_slice(),
-        ),
-        13 => d13::run(
-            std::fs::read_to_string("input/d13.txt")
-                .expect("file not found")
-                .as_str(),
-        ),
-        14 => d14::run(
-            std::fs::read_to_string("input/d14.txt")
-                .expect("file not found")
-                .as_str(),
-        ),
-        15 => d15::run(
-            std::fs::read_to_string("input/d15.txt")
-                .expect("file not found")
-                .as_str(),
-        ),
-        16 => d16::run(
-            std::fs::read_to_string("input/d16.txt")
-                .expect("file not found")
-                .as_str(),
-        ),
-        17 => d17::run(
-            std::fs::read_to_string("input/d17.txt")
-                .expect("file not found")
-                .as_str(),
-        ),
-        18 => d18::run(read_file("input/d18.txt")),
-        19 => d19::run(
-            std::fs::read_to_string("input/d19.txt")
-                .expect("file not found")
-                .as_str(),
-        ),
-        20 => d20::run(
-            std::fs::read_to_string("input/d20.txt")
-                .expect("file not found")
-                .as_str(),
-        ),
-        21 => d21::run(read_file("input/d21.txt")),
-        22 => d22::run(read_file("input/d22.txt")),
-        23 => d23::run(),
-        24 => d24::run(),
-        25 => d25::run(read_file("input/d25.txt")),
-        _ => panic!("invalid input"),
+const USAGE: &str = "Usage:
+    aoc2021 run <day> [--part 1|2] [--input PATH|-] [--trace]
+    aoc2021 all [--json]
+    aoc2021 list
+    aoc2021 verify [<day>] [--answers PATH]
+    aoc2021 decode < TRANSMISSION
+    aoc2021 encode [--length bits|packets] < PACKETS
+    aoc2021 export 19 [--format csv|ply|xyz] [--input PATH|-]";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run {
+        day: u32,
+        part: Option<Part>,
+        source: Source,
+        // Draws the moves of day 23 to stderr.
+        trace: bool,
+    },
+    All {
+        json: bool,
+    },
+    List,
+    Verify {
+        day: Option<u32>,
+        answers: String,
+    },
+    // Decodes BITS packets (day 16) from stdin.
+    Decode,
+    // Encodes BITS packets (day 16), one S-expression per line, from stdin.
+    Encode {
+        length_type: LengthType,
+    },
+    // Writes the assembled beacon map (day 19) to stdout.
+    Export {
+        format: Format,
+        source: Source,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+enum Format {
+    Csv,
+    Ply,
+    Xyz,
+}
+
+#[derive(Debug, PartialEq)]
+enum Source {
+    Default,
+    Stdin,
+    Path(String),
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
     };
 
-    println!("{}, {}", res1, res2);
+    match execute(command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => return Err("missing command".to_string()),
+    };
+
+    match command {
+        "run" => {
+            let (day, mut rest) = match rest.split_first() {
+                Some((day, rest)) => (parse_day(day)?, rest),
+                None => return Err("missing day".to_string()),
+            };
+
+            let mut part = None;
+            let mut source = Source::Default;
+            let mut trace = false;
+
+            while let Some((flag, tail)) = rest.split_first() {
+                if flag == "--trace" {
+                    trace = true;
+                    rest = tail;
+                    continue;
+                }
+
+                let (value, tail) = tail
+                    .split_first()
+                    .ok_or_else(|| format!("missing value for '{}'", flag))?;
+
+                match flag.as_str() {
+                    "--part" => {
+                        part = Some(match value.as_str() {
+                            "1" => Part::One,
+                            "2" => Part::Two,
+                            _ => return Err(format!("invalid part '{}'", value)),
+                        })
+                    }
+                    "--input" => {
+                        source = match value.as_str() {
+                            "-" => Source::Stdin,
+                            path => Source::Path(path.to_string()),
+                        }
+                    }
+                    _ => return Err(format!("unknown option '{}'", flag)),
+                }
+
+                rest = tail;
+            }
+
+            if trace && day != 23 {
+                return Err("'--trace' is only supported for day 23".to_string());
+            }
+
+            Ok(Command::Run {
+                day,
+                part,
+                source,
+                trace,
+            })
+        }
+        "all" => match rest {
+            [] => Ok(Command::All { json: false }),
+            [flag] if flag == "--json" => Ok(Command::All { json: true }),
+            _ => Err(format!("unexpected argument '{}'", rest[0])),
+        },
+        "list" if rest.is_empty() => Ok(Command::List),
+        "list" => Err(format!("unexpected argument '{}'", rest[0])),
+        "decode" if rest.is_empty() => Ok(Command::Decode),
+        "decode" => Err(format!("unexpected argument '{}'", rest[0])),
+        "encode" => match rest {
+            [] => Ok(Command::Encode {
+                length_type: LengthType::Bits,
+            }),
+            [flag, value] if flag == "--length" => match value.as_str() {
+                "bits" => Ok(Command::Encode {
+                    length_type: LengthType::Bits,
+                }),
+                "packets" => Ok(Command::Encode {
+                    length_type: LengthType::Packets,
+                }),
+                _ => Err(format!("invalid length type '{}'", value)),
+            },
+            [flag] if flag == "--length" => Err(format!("missing value for '{}'", flag)),
+            _ => Err(format!("unexpected argument '{}'", rest[0])),
+        },
+        "export" => {
+            let mut rest = match rest.split_first() {
+                Some((day, rest)) if parse_day(day)? == 19 => rest,
+                Some((day, _)) => return Err(format!("day {} cannot be exported", day)),
+                None => return Err("missing day".to_string()),
+            };
+
+            let mut format = Format::Csv;
+            let mut source = Source::Default;
+
+            while let Some((flag, tail)) = rest.split_first() {
+                let (value, tail) = tail
+                    .split_first()
+                    .ok_or_else(|| format!("missing value for '{}'", flag))?;
+
+                match flag.as_str() {
+                    "--format" => {
+                        format = match value.as_str() {
+                            "csv" => Format::Csv,
+                            "ply" => Format::Ply,
+                            "xyz" => Format::Xyz,
+                            _ => return Err(format!("invalid format '{}'", value)),
+                        }
+                    }
+                    "--input" => {
+                        source = match value.as_str() {
+                            "-" => Source::Stdin,
+                            path => Source::Path(path.to_string()),
+                        }
+                    }
+                    _ => return Err(format!("unknown option '{}'", flag)),
+                }
+
+                rest = tail;
+            }
+
+            Ok(Command::Export { format, source })
+        }
+        "verify" => {
+            let mut day = None;
+            let mut answers = answers::PATH.to_string();
+            let mut rest = rest.iter();
+
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--answers" => {
+                        answers = rest
+                            .next()
+                            .ok_or_else(|| format!("missing value for '{}'", arg))?
+                            .to_string()
+                    }
+                    _ if day.is_none() => day = Some(parse_day(arg)?),
+                    _ => return Err(format!("unexpected argument '{}'", arg)),
+                }
+            }
+
+            Ok(Command::Verify { day, answers })
+        }
+        // keep supporting the bare `aoc2021 <day>` invocation
+        day if rest.is_empty() && day.parse::<u32>().is_ok() => Ok(Command::Run {
+            day: parse_day(day)?,
+            part: None,
+            source: Source::Default,
+            trace: false,
+        }),
+        _ => Err(format!("unknown command '{}'", command)),
+    }
 }
 
-fn read_file<T>(file_name: &str) -> Vec<T>
-where
-    T: FromStr,
-    <T as FromStr>::Err: Debug,
-{
-    std::fs::read_to_string(file_name)
-        .expect("file not found")
-        .lines()
-        .map(|x| x.parse().unwrap())
-        .collect()
+fn parse_day(day: &str) -> Result<u32, String> {
+    match day.parse::<u32>() {
+        Ok(day @ 1..=25) => Ok(day),
+        _ => Err(format!(
+            "invalid day '{}', expected a number from 1 to 25",
+            day
+        )),
+    }
 }
 
-fn read_file_with<T>(file_name: &str, f: impl Fn(&str) -> T) -> Vec<T> {
-    std::fs::read_to_string(file_name)
-        .expect("file not found")
-        .lines()
-        .map(f)
-        .collect()
+fn execute(command: Command) -> Result<(), Error> {
+    match command {
+        Command::Run {
+            day,
+            part,
+            source,
+            trace,
+        } => {
+            let day = solution::day(day).expect("day is validated when parsing arguments");
+            let parts = match part {
+                Some(part) => vec![part],
+                None => Part::BOTH.to_vec(),
+            };
+            let input = read_input(day, &source)?;
+
+            let input = solve(day, &input, &parts)?;
+
+            if trace {
+                let solutions = input
+                    .downcast_ref::<d23::Solutions>()
+                    .expect("tracing is validated to be day 23");
+
+                for part in parts {
+                    d23::trace(solutions, part);
+                }
+            }
+        }
+        Command::All { json } => {
+            let start = Instant::now();
+            let timings = DAYS
+                .iter()
+                .map(|day| timing::measure(day, &read_input(day, &Source::Default)?))
+                .collect::<Result<Vec<_>, Error>>()?;
+            let wall_time = start.elapsed();
+
+            if json {
+                println!("{}", timing::json(&timings, wall_time));
+            } else {
+                print!("{}", timing::table(&timings, wall_time));
+            }
+        }
+        Command::List => {
+            for day in DAYS.iter() {
+                println!("{:>2}  {}", day.day, day.title);
+            }
+        }
+        Command::Verify { day, answers } => {
+            let answers = Answers::read(&answers)?;
+            let days = match day {
+                Some(day) => {
+                    vec![solution::day(day).expect("day is validated when parsing arguments")]
+                }
+                None => DAYS.iter().collect(),
+            };
+
+            let mut mismatches = 0;
+
+            for day in days {
+                let input = day.parse(&read_input(day, &Source::Default)?)?;
+
+                for part in Part::BOTH {
+                    let result = day.solve(input.as_ref(), part);
+
+                    match answers.get(day.day, part) {
+                        Some(expected) if expected == result => {
+                            println!("Day {:02} part {}: ok", day.day, part)
+                        }
+                        Some(expected) => {
+                            mismatches += 1;
+                            println!(
+                                "Day {:02} part {}: expected {}, got {}",
+                                day.day, part, expected, result
+                            )
+                        }
+                        None => println!(
+                            "Day {:02} part {}: no expected answer, got {}",
+                            day.day, part, result
+                        ),
+                    }
+                }
+            }
+
+            if mismatches > 0 {
+                return Err(Error::Mismatch(mismatches));
+            }
+        }
+        Command::Decode => {
+            for packet in d16::Decoder::new(io::stdin().lock()) {
+                let packet = packet?;
+                match packet.evaluate() {
+                    Some(value) => println!("{} = {}", packet, value),
+                    None => println!("{} = overflow", packet),
+                }
+            }
+        }
+        Command::Encode { length_type } => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+
+            for (i, line) in input.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let packet = line.parse::<d16::Packet>().map_err(|e| e.at_line(i + 1))?;
+                println!("{}", d16::encode(&packet, length_type)?);
+            }
+        }
+        Command::Export { format, source } => {
+            let day = solution::day(19).expect("day 19 is registered");
+            let input = read_input(day, &source)?;
+            let map = d19::Day19::parse(&input).map_err(|e| e.in_day(day.day))?;
+            let out = io::stdout().lock();
+
+            match format {
+                Format::Csv => map.write_csv(out)?,
+                Format::Ply => map.write_ply(out)?,
+                Format::Xyz => map.write_xyz(out)?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Prints the answers and returns the parsed input.
+fn solve(day: &Day, input: &str, parts: &[Part]) -> Result<Box<dyn Any>, Error> {
+    let input = day.parse(input)?;
+
+    for part in parts {
+        println!("Part {}: {}", part, day.solve(input.as_ref(), *part));
+    }
+
+    Ok(input)
+}
+
+fn read_input(day: &Day, source: &Source) -> io::Result<String> {
+    match source {
+        Source::Default => {
+            let path = day.input_path();
+            std::fs::read_to_string(&path)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))
+        }
+        Source::Path(path) => std::fs::read_to_string(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e))),
+        Source::Stdin => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args_run() {
+        assert_eq!(
+            parse_args(&args(&["run", "16", "--part", "2", "--input", "-"])),
+            Ok(Command::Run {
+                day: 16,
+                part: Some(Part::Two),
+                source: Source::Stdin,
+                trace: false
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["run", "3", "--input", "input/d03.txt"])),
+            Ok(Command::Run {
+                day: 3,
+                part: None,
+                source: Source::Path("input/d03.txt".to_string()),
+                trace: false
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["7"])),
+            Ok(Command::Run {
+                day: 7,
+                part: None,
+                source: Source::Default,
+                trace: false
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["run", "23", "--trace", "--part", "1"])),
+            Ok(Command::Run {
+                day: 23,
+                part: Some(Part::One),
+                source: Source::Default,
+                trace: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_args_all() {
+        assert_eq!(
+            parse_args(&args(&["all"])),
+            Ok(Command::All { json: false })
+        );
+        assert_eq!(
+            parse_args(&args(&["all", "--json"])),
+            Ok(Command::All { json: true })
+        );
+    }
+
+    #[test]
+    fn test_parse_args_verify() {
+        assert_eq!(
+            parse_args(&args(&["verify"])),
+            Ok(Command::Verify {
+                day: None,
+                answers: "answers.toml".to_string()
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["verify", "19", "--answers", "mine.toml"])),
+            Ok(Command::Verify {
+                day: Some(19),
+                answers: "mine.toml".to_string()
+            })
+        );
+        assert!(parse_args(&args(&["verify", "1", "2"])).is_err());
+        assert!(parse_args(&args(&["verify", "--answers"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_decode() {
+        assert_eq!(parse_args(&args(&["decode"])), Ok(Command::Decode));
+    }
+
+    #[test]
+    fn test_parse_args_encode() {
+        assert_eq!(
+            parse_args(&args(&["encode"])),
+            Ok(Command::Encode {
+                length_type: LengthType::Bits
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["encode", "--length", "packets"])),
+            Ok(Command::Encode {
+                length_type: LengthType::Packets
+            })
+        );
+        assert!(parse_args(&args(&["encode", "--length"])).is_err());
+        assert!(parse_args(&args(&["encode", "--length", "bytes"])).is_err());
+        assert!(parse_args(&args(&["encode", "-"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_export() {
+        assert_eq!(
+            parse_args(&args(&["export", "19"])),
+            Ok(Command::Export {
+                format: Format::Csv,
+                source: Source::Default
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["export", "19", "--input", "-", "--format", "ply"])),
+            Ok(Command::Export {
+                format: Format::Ply,
+                source: Source::Stdin
+            })
+        );
+        assert!(parse_args(&args(&["export"])).is_err());
+        assert!(parse_args(&args(&["export", "16"])).is_err());
+        assert!(parse_args(&args(&["export", "19", "--format", "obj"])).is_err());
+        assert!(parse_args(&args(&["export", "19", "--format"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_invalid() {
+        assert!(parse_args(&args(&[])).is_err());
+        assert!(parse_args(&args(&["run"])).is_err());
+        assert!(parse_args(&args(&["run", "26"])).is_err());
+        assert!(parse_args(&args(&["run", "1", "--part", "3"])).is_err());
+        assert!(parse_args(&args(&["run", "1", "--input"])).is_err());
+        assert!(parse_args(&args(&["run", "1", "--verbose", "1"])).is_err());
+        assert!(parse_args(&args(&["run", "1", "--trace"])).is_err());
+        assert!(parse_args(&args(&["all", "1"])).is_err());
+        assert!(parse_args(&args(&["all", "--json", "1"])).is_err());
+        assert!(parse_args(&args(&["decode", "-"])).is_err());
+        assert!(parse_args(&args(&["frobnicate"])).is_err());
+    }
 }
@@ -0,0 +1,63 @@
+use std::thread;
+
+// The number of threads for pairwise loops. Solutions run sequentially
+// unless the crate is built with the `parallel` feature.
+pub fn threads() -> usize {
+    if cfg!(feature = "parallel") {
+        thread::available_parallelism().map_or(1, |n| n.get())
+    } else {
+        1
+    }
+}
+
+// Computes `f(i)` for every `i` in `0..n` on up to `threads` threads and
+// returns the results in order of `i`, just like a sequential map would.
+// Indices are dealt out round robin, which keeps the work balanced for
+// triangular loops where small indices have the most work.
+pub fn map<T, F>(n: usize, threads: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let threads = threads.clamp(1, n.max(1));
+
+    if threads == 1 {
+        return (0..n).map(f).collect();
+    }
+
+    let f = &f;
+    let mut results = thread::scope(|s| {
+        (0..threads)
+            .map(|t| {
+                s.spawn(move || {
+                    (t..n)
+                        .step_by(threads)
+                        .map(|i| (i, f(i)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map() {
+        let expected = (0..100).map(|i| i * i).collect::<Vec<_>>();
+
+        for threads in [0, 1, 2, 3, 8, 200] {
+            assert_eq!(map(100, threads, |i| i * i), expected);
+        }
+
+        assert!(map(0, 4, |i| i).is_empty());
+    }
+}
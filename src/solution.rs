@@ -0,0 +1,176 @@
+use std::{any::Any, str::FromStr};
+
+use crate::{
+    d01, d02, d03, d04, d05, d06, d07, d08, d09, d10, d11, d12, d13, d14, d15, d16, d17, d18, d19,
+    d20, d21, d22, d23, d24, d25,
+    error::{Error, IntoParseError},
+};
+
+pub trait Solution {
+    type Input: 'static;
+
+    // Marks solutions which compute both answers while parsing. The
+    // timing output flags their parse time, as it includes solving.
+    const SOLVES_IN_PARSE: bool = false;
+
+    fn parse(input: &str) -> Result<Self::Input, Error>;
+
+    fn part1(input: &Self::Input) -> usize;
+
+    fn part2(input: &Self::Input) -> usize;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const BOTH: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl std::fmt::Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::One => write!(f, "1"),
+            Self::Two => write!(f, "2"),
+        }
+    }
+}
+
+// A registry entry. The solution's input type is erased so
+// that all days can be driven through the same interface.
+pub struct Day {
+    pub day: u32,
+    pub title: &'static str,
+    pub solves_in_parse: bool,
+    parse: fn(&str) -> Result<Box<dyn Any>, Error>,
+    part1: fn(&dyn Any) -> usize,
+    part2: fn(&dyn Any) -> usize,
+}
+
+impl Day {
+    const fn new<S: Solution>(day: u32, title: &'static str) -> Self {
+        fn parse<S: Solution>(input: &str) -> Result<Box<dyn Any>, Error> {
+            Ok(Box::new(S::parse(input)?))
+        }
+
+        fn part1<S: Solution>(input: &dyn Any) -> usize {
+            S::part1(input.downcast_ref().expect("input of a different day"))
+        }
+
+        fn part2<S: Solution>(input: &dyn Any) -> usize {
+            S::part2(input.downcast_ref().expect("input of a different day"))
+        }
+
+        Self {
+            day,
+            title,
+            solves_in_parse: S::SOLVES_IN_PARSE,
+            parse: parse::<S>,
+            part1: part1::<S>,
+            part2: part2::<S>,
+        }
+    }
+
+    pub fn input_path(&self) -> String {
+        format!("input/d{:02}.txt", self.day)
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Box<dyn Any>, Error> {
+        (self.parse)(input).map_err(|e| e.in_day(self.day))
+    }
+
+    pub fn solve(&self, input: &dyn Any, part: Part) -> usize {
+        match part {
+            Part::One => (self.part1)(input),
+            Part::Two => (self.part2)(input),
+        }
+    }
+}
+
+#[rustfmt::skip]
+pub static DAYS: [Day; 25] = [
+    Day::new::<d01::Day01>(1, "Sonar Sweep"),
+    Day::new::<d02::Day02>(2, "Dive!"),
+    Day::new::<d03::Day03>(3, "Binary Diagnostic"),
+    Day::new::<d04::Day04>(4, "Giant Squid"),
+    Day::new::<d05::Day05>(5, "Hydrothermal Venture"),
+    Day::new::<d06::Day06>(6, "Lanternfish"),
+    Day::new::<d07::Day07>(7, "The Treachery of Whales"),
+    Day::new::<d08::Day08>(8, "Seven Segment Search"),
+    Day::new::<d09::Day09>(9, "Smoke Basin"),
+    Day::new::<d10::Day10>(10, "Syntax Scoring"),
+    Day::new::<d11::Day11>(11, "Dumbo Octopus"),
+    Day::new::<d12::Day12>(12, "Passage Pathing"),
+    Day::new::<d13::Day13>(13, "Transparent Origami"),
+    Day::new::<d14::Day14>(14, "Extended Polymerization"),
+    Day::new::<d15::Day15>(15, "Chiton"),
+    Day::new::<d16::Day16>(16, "Packet Decoder"),
+    Day::new::<d17::Day17>(17, "Trick Shot"),
+    Day::new::<d18::Day18>(18, "Snailfish"),
+    Day::new::<d19::Day19>(19, "Beacon Scanner"),
+    Day::new::<d20::Day20>(20, "Trench Map"),
+    Day::new::<d21::Day21>(21, "Dirac Dice"),
+    Day::new::<d22::Day22>(22, "Reactor Reboot"),
+    Day::new::<d23::Day23>(23, "Amphipod"),
+    Day::new::<d24::Day24>(24, "Arithmetic Logic Unit"),
+    Day::new::<d25::Day25>(25, "Sea Cucumber"),
+];
+
+pub fn day(day: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+pub fn parse_lines<T>(input: &str) -> Result<Vec<T>, Error>
+where
+    T: FromStr,
+    <T as FromStr>::Err: IntoParseError,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            line.parse()
+                .map_err(|e: T::Err| Error::Parse(e.into_parse_error(line).at_line(i + 1)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        assert_eq!(DAYS.len(), 25);
+        DAYS.iter()
+            .enumerate()
+            .for_each(|(i, d)| assert_eq!(d.day, i as u32 + 1));
+
+        assert_eq!(day(16).unwrap().title, "Packet Decoder");
+        assert!(day(26).is_none());
+    }
+
+    #[test]
+    fn test_solve() {
+        let day = day(1).unwrap();
+        let input = day
+            .parse("199\n200\n208\n210\n200\n207\n240\n269\n260\n263")
+            .unwrap();
+
+        assert_eq!(day.solve(input.as_ref(), Part::One), 7);
+        assert_eq!(day.solve(input.as_ref(), Part::Two), 5);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let e = day(1).unwrap().parse("199\n200\n2o8").err().unwrap();
+
+        assert_eq!(
+            e.to_string(),
+            "parse error in day 01, line 3, column 1: expected a number, found '2o8'"
+        );
+    }
+}
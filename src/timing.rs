@@ -0,0 +1,208 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    error::Error,
+    solution::{Day, Part},
+};
+
+pub struct Timing {
+    pub day: u32,
+    pub title: &'static str,
+    // The parse time includes solving both parts.
+    pub solves_in_parse: bool,
+    pub parse: Duration,
+    pub parts: [(usize, Duration); 2],
+}
+
+impl Timing {
+    pub fn total(&self) -> Duration {
+        self.parse + self.parts[0].1 + self.parts[1].1
+    }
+}
+
+pub fn measure(day: &'static Day, input: &str) -> Result<Timing, Error> {
+    let start = Instant::now();
+    let parsed = day.parse(input)?;
+    let parse = start.elapsed();
+
+    let mut parts = [(0, Duration::ZERO); 2];
+
+    for (i, part) in Part::BOTH.into_iter().enumerate() {
+        let start = Instant::now();
+        let result = day.solve(parsed.as_ref(), part);
+        parts[i] = (result, start.elapsed());
+    }
+
+    Ok(Timing {
+        day: day.day,
+        title: day.title,
+        solves_in_parse: day.solves_in_parse,
+        parse,
+        parts,
+    })
+}
+
+pub fn table(timings: &[Timing], wall_time: Duration) -> String {
+    let title_width = timings.iter().map(|t| t.title.len()).max().unwrap_or(0);
+    let result_width = timings
+        .iter()
+        .flat_map(|t| t.parts.iter().map(|(result, _)| result.to_string().len()))
+        .max()
+        .unwrap_or(0)
+        .max("Part 1".len());
+
+    let mut table = format!(
+        "{:>3}  {:<tw$}  {:>rw$}  {:>rw$}  {:>10}  {:>10}  {:>10}  {:>10}\n",
+        "Day",
+        "Title",
+        "Part 1",
+        "Part 2",
+        "Parse",
+        "Part 1",
+        "Part 2",
+        "Total",
+        tw = title_width,
+        rw = result_width
+    );
+
+    for t in timings {
+        let mark = if t.solves_in_parse { "*" } else { "" };
+
+        table.push_str(&format!(
+            "{:>3}  {:<tw$}  {:>rw$}  {:>rw$}  {:>10}  {:>10}  {:>10}  {:>10}\n",
+            t.day,
+            t.title,
+            t.parts[0].0,
+            t.parts[1].0,
+            format_duration(t.parse) + mark,
+            format_duration(t.parts[0].1),
+            format_duration(t.parts[1].1),
+            format_duration(t.total()),
+            tw = title_width,
+            rw = result_width
+        ));
+    }
+
+    let solve_time = timings.iter().map(Timing::total).sum::<Duration>();
+    // Align the summary with the last column.
+    let width = 3 + title_width + 2 * result_width + 4 * 10 + 7 * 2;
+
+    table.push_str(&format!(
+        "{:>w$}\n{:>w$}\n",
+        format!("solve time {}", format_duration(solve_time)),
+        format!("wall time {}", format_duration(wall_time)),
+        w = width
+    ));
+
+    if timings.iter().any(|t| t.solves_in_parse) {
+        table.push_str("* parsing includes solving both parts\n");
+    }
+
+    table
+}
+
+pub fn json(timings: &[Timing], wall_time: Duration) -> String {
+    let days = timings
+        .iter()
+        .map(|t| {
+            format!(
+                r#"{{"day":{},"title":"{}","part1":{},"part2":{},"solves_in_parse":{},"parse_ns":{},"part1_ns":{},"part2_ns":{},"total_ns":{}}}"#,
+                t.day,
+                t.title.replace('\\', "\\\\").replace('"', "\\\""),
+                t.parts[0].0,
+                t.parts[1].0,
+                t.solves_in_parse,
+                t.parse.as_nanos(),
+                t.parts[0].1.as_nanos(),
+                t.parts[1].1.as_nanos(),
+                t.total().as_nanos()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        r#"{{"days":[{}],"wall_time_ns":{}}}"#,
+        days,
+        wall_time.as_nanos()
+    )
+}
+
+fn format_duration(d: Duration) -> String {
+    let nanos = d.as_nanos();
+
+    if nanos < 1_000 {
+        format!("{}ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.1}µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.1}ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2}s", nanos as f64 / 1e9)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timings() -> Vec<Timing> {
+        vec![
+            Timing {
+                day: 1,
+                title: "Sonar Sweep",
+                solves_in_parse: false,
+                parse: Duration::from_micros(120),
+                parts: [
+                    (1316, Duration::from_nanos(800)),
+                    (1344, Duration::from_micros(5)),
+                ],
+            },
+            Timing {
+                day: 2,
+                title: "Dive!",
+                solves_in_parse: true,
+                parse: Duration::from_millis(2),
+                parts: [
+                    (2322630, Duration::from_millis(1500)),
+                    (2105273490, Duration::from_secs(2)),
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_nanos(800)), "800ns");
+        assert_eq!(format_duration(Duration::from_nanos(1_250)), "1.2µs");
+        assert_eq!(format_duration(Duration::from_micros(2_500)), "2.5ms");
+        assert_eq!(format_duration(Duration::from_millis(3_500)), "3.50s");
+    }
+
+    #[test]
+    fn test_table() {
+        let table = table(&timings(), Duration::from_secs(4));
+
+        assert_eq!(
+            table,
+            "\
+Day  Title            Part 1      Part 2       Parse      Part 1      Part 2       Total
+  1  Sonar Sweep        1316        1344     120.0µs       800ns       5.0µs     125.8µs
+  2  Dive!           2322630  2105273490      2.0ms*       1.50s       2.00s       3.50s
+                                                                        solve time 3.50s
+                                                                         wall time 4.00s
+* parsing includes solving both parts
+"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let json = json(&timings()[..1], Duration::from_secs(4));
+
+        assert_eq!(
+            json,
+            r#"{"days":[{"day":1,"title":"Sonar Sweep","part1":1316,"part2":1344,"solves_in_parse":false,"parse_ns":120000,"part1_ns":800,"part2_ns":5000,"total_ns":125800}],"wall_time_ns":4000000000}"#
+        );
+    }
+}